@@ -0,0 +1,312 @@
+use cosmwasm_schema::cw_serde;
+use sha3::{Digest, Keccak256};
+
+use crate::ContractError;
+
+/// Checks the receiver of a ticket against the address format of its target chain, so that a
+/// typo is rejected before the tokens are burned.
+#[cw_serde]
+pub enum ReceiverValidator {
+    // segwit (bech32 / bech32m, taproot included) and legacy base58check addresses
+    Bitcoin { network: BitcoinNetwork },
+    // 0x prefixed hex, EIP-55 checksum enforced on mixed case addresses
+    Evm,
+    // textual ICP principal
+    Icp,
+    // base58 ed25519 public key
+    Solana,
+    // bech32 account address with the given human readable part
+    Cosmos { hrp: String },
+}
+
+#[cw_serde]
+pub enum BitcoinNetwork {
+    Mainnet,
+    Testnet,
+    Regtest,
+}
+
+impl Eq for BitcoinNetwork {}
+
+impl BitcoinNetwork {
+    fn segwit_hrp(&self) -> &'static str {
+        match self {
+            BitcoinNetwork::Mainnet => "bc",
+            BitcoinNetwork::Testnet => "tb",
+            BitcoinNetwork::Regtest => "bcrt",
+        }
+    }
+
+    // (p2pkh, p2sh) version bytes
+    fn base58_versions(&self) -> (u8, u8) {
+        match self {
+            BitcoinNetwork::Mainnet => (0x00, 0x05),
+            BitcoinNetwork::Testnet | BitcoinNetwork::Regtest => (0x6f, 0xc4),
+        }
+    }
+}
+
+impl Eq for ReceiverValidator {}
+
+impl ReceiverValidator {
+    pub fn validate(&self, receiver: &str) -> Result<(), ContractError> {
+        let valid = match self {
+            ReceiverValidator::Bitcoin { network } => is_bitcoin_address(network, receiver),
+            ReceiverValidator::Evm => is_evm_address(receiver),
+            ReceiverValidator::Icp => is_icp_principal(receiver),
+            ReceiverValidator::Solana => is_solana_address(receiver),
+            ReceiverValidator::Cosmos { hrp } => is_cosmos_address(hrp, receiver),
+        };
+        if !valid {
+            return Err(ContractError::InvalidReceiver(receiver.to_string()));
+        }
+        Ok(())
+    }
+}
+
+fn is_bitcoin_address(network: &BitcoinNetwork, address: &str) -> bool {
+    if let Ok((hrp, _, _)) = bech32::segwit::decode(address) {
+        return hrp.to_lowercase() == network.segwit_hrp();
+    }
+
+    let (p2pkh, p2sh) = network.base58_versions();
+    match bs58::decode(address).with_check(None).into_vec() {
+        Ok(payload) => payload.len() == 21 && (payload[0] == p2pkh || payload[0] == p2sh),
+        Err(_) => false,
+    }
+}
+
+fn is_evm_address(address: &str) -> bool {
+    let hex = match address.strip_prefix("0x") {
+        Some(hex) if hex.len() == 40 => hex,
+        _ => return false,
+    };
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return false;
+    }
+    let lowercase = hex.to_ascii_lowercase();
+    if hex == lowercase || hex == hex.to_ascii_uppercase() {
+        return true;
+    }
+
+    // EIP-55: a letter is uppercase when the matching nibble of keccak256(lowercase) is >= 8
+    let hash = Keccak256::digest(lowercase.as_bytes());
+    hex.chars().enumerate().all(|(i, c)| {
+        let nibble = (hash[i / 2] >> (4 * (1 - i % 2))) & 0x0f;
+        !c.is_ascii_alphabetic() || c.is_ascii_uppercase() == (nibble >= 8)
+    })
+}
+
+fn is_icp_principal(text: &str) -> bool {
+    const ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+    // groups of five characters separated by dashes, the last one may be shorter
+    let groups: Vec<&str> = text.split('-').collect();
+    let (last, full) = groups.split_last().expect("split yields at least one item");
+    if last.is_empty() || last.len() > 5 || full.iter().any(|g| g.len() != 5) {
+        return false;
+    }
+
+    let mut bytes = vec![];
+    let (mut buffer, mut bits) = (0u32, 0u32);
+    for c in groups.concat().bytes() {
+        let value = match ALPHABET.iter().position(|a| *a == c) {
+            Some(value) => value as u32,
+            None => return false,
+        };
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    // leftover bits of the unpadded encoding must be zero
+    if buffer != 0 || bytes.len() < 4 || bytes.len() > 4 + 29 {
+        return false;
+    }
+
+    let (checksum, principal) = bytes.split_at(4);
+    crc32fast::hash(principal).to_be_bytes() == checksum
+}
+
+fn is_solana_address(address: &str) -> bool {
+    matches!(bs58::decode(address).into_vec(), Ok(bytes) if bytes.len() == 32)
+}
+
+fn is_cosmos_address(hrp: &str, address: &str) -> bool {
+    match bech32::decode(address) {
+        Ok((decoded_hrp, data)) => {
+            decoded_hrp.to_lowercase() == hrp.to_lowercase()
+                && (data.len() == 20 || data.len() == 32)
+        }
+        Err(_) => false,
+    }
+}
+
+#[test]
+pub fn test_bitcoin_receiver() {
+    let mainnet = ReceiverValidator::Bitcoin {
+        network: BitcoinNetwork::Mainnet,
+    };
+    for address in [
+        // p2pkh, p2sh
+        "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2",
+        "3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy",
+        // p2wpkh, BIP-173
+        "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+        "BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4",
+        // p2tr, BIP-86
+        "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr",
+    ] {
+        assert!(mainnet.validate(address).is_ok(), "{}", address);
+    }
+
+    for address in [
+        // checksum typos
+        "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN3",
+        "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t5",
+        "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcq",
+        // testnet addresses on mainnet
+        "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx",
+        "mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn",
+        // mixed case
+        "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kV8F3T4",
+        "",
+    ] {
+        assert!(mainnet.validate(address).is_err(), "{}", address);
+    }
+
+    let testnet = ReceiverValidator::Bitcoin {
+        network: BitcoinNetwork::Testnet,
+    };
+    assert!(testnet
+        .validate("tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx")
+        .is_ok());
+    assert!(testnet
+        .validate("mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn")
+        .is_ok());
+    assert!(testnet
+        .validate("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4")
+        .is_err());
+
+    // BIP-350: witness v1 with a bech32 checksum, witness v0 with a bech32m checksum
+    for address in [
+        "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqh2y7hd",
+        "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kemeawh",
+    ] {
+        assert!(mainnet.validate(address).is_err(), "{}", address);
+    }
+}
+
+#[test]
+pub fn test_evm_receiver() {
+    // EIP-55
+    for address in [
+        "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+        "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+        "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+        "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed",
+        "0x5AAEB6053F3E94C9B9A09F33669435E7EF1BEAED",
+    ] {
+        assert!(
+            ReceiverValidator::Evm.validate(address).is_ok(),
+            "{}",
+            address
+        );
+    }
+
+    for address in [
+        "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD",
+        "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeA",
+        "5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+        "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeg",
+    ] {
+        assert!(
+            ReceiverValidator::Evm.validate(address).is_err(),
+            "{}",
+            address
+        );
+    }
+}
+
+#[test]
+pub fn test_icp_receiver() {
+    for principal in [
+        "aaaaa-aa",
+        "2vxsx-fae",
+        "ryjl3-tyaaa-aaaaa-aaaba-cai",
+        "rrkah-fqaaa-aaaaa-aaaaq-cai",
+    ] {
+        assert!(
+            ReceiverValidator::Icp.validate(principal).is_ok(),
+            "{}",
+            principal
+        );
+    }
+
+    for principal in [
+        "ryjl3-tyaaa-aaaaa-aaaba-caj",
+        "ryjl3tyaaaaaaaaaaabacai",
+        "ryjl3-tyaaa-aaaaa-aaaba-cai-",
+        "RYJL3-TYAAA-AAAAA-AAABA-CAI",
+        "",
+    ] {
+        assert!(
+            ReceiverValidator::Icp.validate(principal).is_err(),
+            "{}",
+            principal
+        );
+    }
+}
+
+#[test]
+pub fn test_solana_receiver() {
+    for address in [
+        "11111111111111111111111111111111",
+        "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+    ] {
+        assert!(
+            ReceiverValidator::Solana.validate(address).is_ok(),
+            "{}",
+            address
+        );
+    }
+
+    for address in [
+        "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5D",
+        "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+        "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5D0",
+    ] {
+        assert!(
+            ReceiverValidator::Solana.validate(address).is_err(),
+            "{}",
+            address
+        );
+    }
+}
+
+#[test]
+pub fn test_cosmos_receiver() {
+    let osmosis = ReceiverValidator::Cosmos {
+        hrp: "osmo".to_string(),
+    };
+    let hrp = bech32::Hrp::parse("osmo").unwrap();
+    let account = bech32::encode::<bech32::Bech32>(hrp, &[7u8; 20]).unwrap();
+    let contract = bech32::encode::<bech32::Bech32>(hrp, &[7u8; 32]).unwrap();
+    assert!(osmosis.validate(&account).is_ok());
+    assert!(osmosis.validate(&contract).is_ok());
+
+    let cosmos_hub =
+        bech32::encode::<bech32::Bech32>(bech32::Hrp::parse("cosmos").unwrap(), &[7u8; 20])
+            .unwrap();
+    assert!(osmosis.validate(&cosmos_hub).is_err());
+    let short = bech32::encode::<bech32::Bech32>(hrp, &[7u8; 8]).unwrap();
+    assert!(osmosis.validate(&short).is_err());
+    let mut typo = account.clone();
+    typo.pop();
+    typo.push(if account.ends_with('q') { 'p' } else { 'q' });
+    assert!(osmosis.validate(&typo).is_err());
+}
@@ -1,14 +1,16 @@
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, GetTargetChainFeeResponse, InstantiateMsg, QueryMsg};
+use crate::forward::ibc_lifecycle_complete;
+use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, SudoMsg};
 use crate::route::ChainState;
 use crate::state::{read_state, State, STATE};
+use crate::types::{MintTicket, TicketParams};
 use std::collections::{BTreeMap, BTreeSet};
 
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
     to_json_binary, Binary, Deps, DepsMut, Empty, Env, Event, MessageInfo, Response, StdError,
-    StdResult,
+    StdResult, Storage,
 };
 use cw2::set_contract_version;
 use semver::Version;
@@ -18,7 +20,7 @@ const CONTRACT_NAME: &str = "crates.io:omnity-port-cosmos";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 #[entry_point]
-pub fn migrate(deps: DepsMut, _env: Env, _msg: Empty) -> Result<Response, ContractError> {
+pub fn migrate(deps: DepsMut, env: Env, _msg: Empty) -> Result<Response, ContractError> {
     let new_semver_version: Version = CONTRACT_VERSION.parse()?;
     let old_contract_version = cw2::get_contract_version(deps.storage)?;
     let old_semver_version: Version = old_contract_version.version.parse()?;
@@ -35,6 +37,11 @@ pub fn migrate(deps: DepsMut, _env: Env, _msg: Empty) -> Result<Response, Contra
     cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     // do any desired state migrations...
+    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+        state.migrate_token_ids()?;
+        state.migrate_token_denoms(&env.contract.address);
+        Ok(state)
+    })?;
 
     Ok(Response::default())
 }
@@ -64,6 +71,24 @@ pub fn instantiate(
         allbtc_token_denom: Default::default(),
         allbtc_swap_pool_id: Default::default(),
         runes_replaced_id_map: Default::default(),
+        token_ids: Default::default(),
+        token_denoms: Default::default(),
+        chain_key_tokens: Default::default(),
+        escrow_tokens: Default::default(),
+        escrow_balances: Default::default(),
+        adopted_denoms: Default::default(),
+        removed_tokens: Default::default(),
+        operation_sequence: Default::default(),
+        timelock_delay: Default::default(),
+        attestors: Default::default(),
+        hub_key: Default::default(),
+        relay_reward: Default::default(),
+        ibc_port: Default::default(),
+        ibc_channel: Default::default(),
+        hook_fee_factors: Default::default(),
+        archived_escrow: Default::default(),
+        max_memo_length: None,
+        token_routes: Default::default(),
     };
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     STATE.save(deps.storage, &state)?;
@@ -80,25 +105,40 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
-    let chain_state_active = read_state(deps.storage, |state| {
-        state.chain_state == ChainState::Active
-    });
-    if !chain_state_active {
-        return Err(ContractError::ChainDeactive);
-    }
+    execute::check_chain_active(deps.as_ref())?;
     let contract = env.contract.address.clone();
     let response = match msg {
         ExecuteMsg::ExecDirective { seq, directive } => {
             execute::exec_directive(deps, env, info, seq, directive)
         }
+        ExecuteMsg::ExecSignedDirective {
+            seq,
+            directive,
+            signature,
+        } => execute::exec_signed_directive(deps, env, info, seq, directive, signature),
         ExecuteMsg::PrivilegeMintToken {
             ticket_id,
             token_id,
             receiver,
             amount,
             transmuter,
+            signature,
+            channel,
+            recovery,
         } => execute::privilege_mint_token(
-            deps, env, info, ticket_id, token_id, receiver, amount, transmuter,
+            deps,
+            env,
+            info,
+            MintTicket {
+                ticket_id,
+                token_id,
+                receiver,
+                amount,
+                transmuter,
+                channel,
+                recovery,
+            },
+            signature,
         ),
         ExecuteMsg::RedeemToken {
             token_id,
@@ -123,15 +163,30 @@ pub fn execute(
             deps,
             env,
             info,
-            token_id,
-            sender,
-            receiver,
-            amount,
-            target_chain,
-            action,
-            memo,
+            TicketParams {
+                token_id,
+                sender,
+                receiver,
+                amount,
+                target_chain,
+                action,
+                memo,
+                from_hook: false,
+            },
         ),
-        ExecuteMsg::UpdateRoute { route } => execute::update_route(deps, info, route),
+        ExecuteMsg::IbcHook {
+            channel,
+            original_sender,
+            recovery,
+            action,
+        } => execute::ibc_hook(deps, env, info, channel, original_sender, recovery, action),
+        ExecuteMsg::ExecuteHook {
+            original_sender,
+            recovery,
+            action,
+        } => execute::execute_hook(deps, env, info, original_sender, recovery, action),
+        ExecuteMsg::ClaimBalance {} => execute::claim_balance(deps, info),
+        ExecuteMsg::UpdateRoute { route } => execute::update_route(deps, env, info, route),
         ExecuteMsg::RedeemSetting {
             token_id,
             target_chain,
@@ -146,6 +201,55 @@ pub fn execute(
         } => execute::execute_update_token_msg(
             deps, env, &info, token_id, name, symbol, decimals, icon,
         ),
+        ExecuteMsg::SetChainKeyToken {
+            token_id,
+            chain_key_token,
+        } => execute::set_chain_key_token(deps, info, token_id, chain_key_token),
+        ExecuteMsg::Approve {
+            operator,
+            expires_at,
+        } => execute::approve(deps, env, info, operator, expires_at),
+        ExecuteMsg::RevokeApproval { operator } => execute::revoke_approval(deps, info, operator),
+        ExecuteMsg::SetReceiverValidator {
+            chain_id,
+            validator,
+        } => execute::set_receiver_validator(deps, info, chain_id, validator),
+        ExecuteMsg::SetTokenRoutes { token_id, routes } => {
+            execute::set_token_routes(deps, info, token_id, routes)
+        }
+        ExecuteMsg::SetHookFeeFactor { token_id, factor } => {
+            execute::set_hook_fee_factor(deps, info, token_id, factor)
+        }
+        ExecuteMsg::SetMaxMemoLength { max_memo_length } => {
+            execute::set_max_memo_length(deps, info, max_memo_length)
+        }
+        ExecuteMsg::SetEscrowToken { token_id, denom } => {
+            execute::set_escrow_token(deps, info, token_id, denom)
+        }
+        ExecuteMsg::SetAdoptedDenom { token_id, denom } => {
+            execute::set_adopted_denom(deps, env, info, token_id, denom)
+        }
+        ExecuteMsg::TransferDenomAdmin {
+            token_id,
+            new_admin,
+        } => execute::transfer_denom_admin(deps, env, info, Some(token_id), new_admin),
+        ExecuteMsg::TransferAllDenomAdmins { new_admin } => {
+            execute::transfer_denom_admin(deps, env, info, None, new_admin)
+        }
+        ExecuteMsg::SetTimelockDelay { delay_secs } => {
+            execute::set_timelock_delay(deps, env, info, delay_secs)
+        }
+        ExecuteMsg::SetRelayReward { reward } => execute::set_relay_reward(deps, env, info, reward),
+        ExecuteMsg::ClaimRelayRewards {} => execute::claim_relay_rewards(deps, env, info),
+        ExecuteMsg::SetIbcPort { port } => execute::set_ibc_port(deps, env, info, port),
+        ExecuteMsg::SetAttestors { attestors } => {
+            execute::set_attestors(deps, env, info, attestors)
+        }
+        ExecuteMsg::ExecuteOperation { id } => execute::execute_operation(deps, env, info, id),
+        ExecuteMsg::CancelOperation { id } => execute::cancel_operation(deps, info, id),
+        ExecuteMsg::ArchiveToken { token_id } => {
+            execute::archive_token(deps.storage, &env, token_id)
+        }
         ExecuteMsg::RefundToken {
             denom,
             receiver,
@@ -162,46 +266,112 @@ pub fn execute(
     Ok(response.add_event(Event::new("execute_msg").add_attribute("contract", contract)))
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn sudo(deps: DepsMut, _env: Env, msg: SudoMsg) -> Result<Response, ContractError> {
+    match msg {
+        SudoMsg::IbcLifecycleComplete(msg) => ibc_lifecycle_complete(deps.storage, msg),
+    }
+}
+
 pub mod execute {
-    use cosmwasm_std::{Addr, Attribute, BankMsg, CosmosMsg, Event, SubMsg};
+    use cosmwasm_std::{
+        from_json, Addr, Api, Attribute, BankMsg, CosmosMsg, Event, IbcMsg, Order, SubMsg, Uint128,
+        WasmMsg,
+    };
     use osmosis_std::types::{
-        cosmos::bank::v1beta1::MsgSend, osmosis::poolmanager::v1beta1::MsgSwapExactAmountIn,
+        cosmos::bank::v1beta1::MsgSend,
+        osmosis::{
+            poolmanager::v1beta1::MsgSwapExactAmountIn, tokenfactory::v1beta1::TokenfactoryQuerier,
+        },
     };
     use prost::Message;
 
     use crate::{
-        cosmos::{
-            bank::v1beta1::{DenomUnit, Metadata},
-            base::v1beta1::Coin,
-        },
+        address::ReceiverValidator,
+        cosmos::base::v1beta1::Coin,
+        denom_metadata::{denom_metadata, is_valid_denom},
+        forward::{forward_beneficiary, forward_msg, take_claimable, Forward, CLAIMABLE_TOTALS},
+        hooks::{intermediate_sender, HookAction, HookRecovery},
+        hub_key::{directive_message, mint_ticket_message},
+        ibc::deliver_ticket,
+        memo::validate_memo,
         msg::reply_msg_id,
-        osmosis::tokenfactory::v1beta1::{MsgBurn, MsgCreateDenom, MsgMint, MsgSetDenomMetadata},
-        route::{Directive, Factor, Token},
-        state::{read_state, GenerateTicketReq, IcpChainKeyToken},
-        types::{MintTokenPayload, RedeemAllBTC},
+        osmosis::tokenfactory::v1beta1::{
+            MsgBurn, MsgChangeAdmin, MsgCreateDenom, MsgMint, MsgSetDenomMetadata,
+        },
+        reply::redeem_requested_event,
+        route::{Directive, Factor, RemoveToken, Token, TokenId},
+        state::{
+            read_state, Approval, ArchivedToken, AttestorSet, CounterpartPort, GenerateTicketReq,
+            IcpChainKeyToken, MintAttestation, PendingOperation, RelayerStats, TimelockedAction,
+            TokenMetadataChange, TokenRoutes, TokenSunset, APPROVALS, ARCHIVED_TOKENS,
+            MINT_ATTESTATIONS, MIN_TIMELOCK_SECS, PENDING_OPERATIONS, RELAYERS, RELAYER_RANKING,
+            TOKEN_METADATA_HISTORY,
+        },
+        types::{MintTicket, MintTokenPayload, RedeemAllBTC, TicketParams},
     };
 
     use super::*;
 
-    pub fn token_denom(address: String, token_id: String) -> String {
-        let denom = format!("factory/{}/{}", address, token_id);
-        return denom;
+    /// Denom tokenfactory gives to `subdenom` created by the contract. Only used when a token
+    /// is added, every other path reads the denom recorded in `State::token_denoms`.
+    pub fn factory_denom(contract_addr: &Addr, subdenom: &str) -> String {
+        format!("factory/{}/{}", contract_addr, subdenom)
     }
 
     pub fn exec_directive(
-        mut deps: DepsMut,
+        deps: DepsMut,
         env: Env,
         info: MessageInfo,
         seq: u64,
         directive: Directive,
     ) -> Result<Response, ContractError> {
-        let mut response = Response::new();
-
         if read_state(deps.storage, |s| {
             s.route != info.sender && s.admin != info.sender
         }) {
             return Err(ContractError::Unauthorized);
         }
+        // once the hub signs, a stolen route key must not be enough
+        if read_state(deps.storage, |s| s.hub_key.is_some()) {
+            return Err(ContractError::InvalidSignature(
+                "directives must be signed by the hub key".to_string(),
+            ));
+        }
+        handle_directive(deps, env, info, seq, directive)
+    }
+
+    /// Runs a directive signed by the hub key, which needs no trusted sender. The signature
+    /// covers the bytes as relayed, they are only parsed once verified.
+    pub fn exec_signed_directive(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        seq: u64,
+        directive: Binary,
+        signature: Binary,
+    ) -> Result<Response, ContractError> {
+        let (hub_key, chain_id) =
+            read_state(deps.storage, |s| (s.hub_key.clone(), s.chain_id.clone()));
+        let hub_key = hub_key.ok_or(ContractError::InvalidSignature(
+            "no hub key is registered".to_string(),
+        ))?;
+        hub_key.verify(
+            deps.api,
+            &directive_message(&env.contract.address, &chain_id, seq, &directive),
+            &signature,
+        )?;
+        let directive: Directive = from_json(&directive)?;
+        handle_directive(deps, env, info, seq, directive)
+    }
+
+    fn handle_directive(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        seq: u64,
+        directive: Directive,
+    ) -> Result<Response, ContractError> {
+        let mut response = Response::new();
 
         if read_state(deps.storage, |state| {
             state.handled_directives.contains(&seq)
@@ -211,16 +381,7 @@ pub mod execute {
 
         match directive {
             Directive::AddToken(mut token) => {
-                if token.token_id.contains("•") {
-                    let replaced_runes_id = token.token_id.replace("•", ".");
-                    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
-                        state
-                            .runes_replaced_id_map
-                            .insert(token.token_id.clone(), replaced_runes_id.clone());
-                        Ok(state)
-                    })?;
-                    token.token_id = replaced_runes_id;
-                }
+                register_token_id(deps.storage, &mut token)?;
                 response = add_token(&mut deps, env, info, token)?;
             }
             Directive::UpdateFee(factor) => {
@@ -240,70 +401,48 @@ pub mod execute {
                     Ok(state)
                 })?;
             }
-            Directive::AddChain(chain) | Directive::UpdateChain(chain) => {
+            Directive::AddChain(mut chain) | Directive::UpdateChain(mut chain) => {
                 STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
                     if chain.chain_id == state.chain_id {
                         state.chain_state = chain.chain_state.clone();
                     }
+                    if chain.receiver_validator.is_none() {
+                        chain.receiver_validator = state
+                            .counterparties
+                            .get(&chain.chain_id)
+                            .and_then(|c| c.receiver_validator.clone());
+                    }
 
                     state.counterparties.insert(chain.chain_id.clone(), chain);
                     Ok(state)
                 })?;
             }
             Directive::UpdateToken(mut token) => {
-                if token.token_id.contains("•") {
-                    let replaced_runes_id = token.token_id.replace("•", ".");
-                    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
-                        state
-                            .runes_replaced_id_map
-                            .insert(token.token_id.clone(), replaced_runes_id.clone());
-                        Ok(state)
-                    })?;
-                    token.token_id = replaced_runes_id;
+                register_token_id(deps.storage, &mut token)?;
+                let previous = read_state(deps.storage, |s| s.tokens.get(&token.token_id).cloned());
+                match previous {
+                    None => response = add_token(&mut deps, env, info, token)?,
+                    Some(previous) => {
+                        response =
+                            apply_token_update(deps.branch(), &env, &info.sender, previous, token)?;
+                    }
                 }
-                if read_state(deps.storage, |s| !s.tokens.contains_key(&token.token_id)) {
-                    response = add_token(&mut deps, env, info, token)?;
-                } else {
-                    let sender = env.contract.address.to_string();
-
-                    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
-                        state.tokens.insert(token.token_id.clone(), token.clone());
-                        Ok(state)
-                    })?;
-
-                    let token_base_denom =
-                        token_denom(env.contract.address.to_string(), token.token_id);
-                    let set_denom_metadata_msg = MsgSetDenomMetadata {
-                        sender: sender.clone(),
-                        metadata: Some(Metadata {
-                            description: token.name.clone(),
-                            denom_units: vec![
-                                DenomUnit {
-                                    denom: token_base_denom.clone(),
-                                    exponent: 0,
-                                    aliases: vec![],
-                                },
-                                DenomUnit {
-                                    denom: token.symbol.clone(),
-                                    exponent: token.decimals as u32,
-                                    aliases: vec![],
-                                },
-                            ],
-                            base: token_base_denom,
-                            display: token.symbol.clone(),
-                            name: token.name,
-                            symbol: token.symbol,
-                            uri: token.icon.unwrap_or("".to_string()),
-                            uri_hash: "".to_string(),
-                        }),
-                    };
-                    let update_msg = CosmosMsg::Stargate {
-                        type_url: "/osmosis.tokenfactory.v1beta1.MsgSetDenomMetadata".to_string(),
-                        value: set_denom_metadata_msg.encode_to_vec().into(),
-                    };
-
-                    response = response.add_message(update_msg);
+            }
+            Directive::RemoveToken(remove_token) => {
+                response = remove_token_directive(deps.api, deps.storage, &env, remove_token)?;
+            }
+            Directive::UpdateHubKey(hub_key) => {
+                if let Some(hub_key) = &hub_key {
+                    hub_key.validate()?;
                 }
+                // signed tickets skip the attestors, so a new key waits out the timelock
+                let scheduled = schedule_operation(
+                    deps.storage,
+                    &env,
+                    &info.sender,
+                    TimelockedAction::SetHubKey { hub_key },
+                )?;
+                response = response.add_events(scheduled.events);
             }
             Directive::ToggleChainState(toggle_state) => {
                 STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
@@ -328,385 +467,1921 @@ pub mod execute {
             .add_event(Event::new("DirectiveExecuted").add_attribute("sequence", seq.to_string())))
     }
 
+    /// Records the Omnity id of `token` and switches it to the local id it is stored under.
+    fn register_token_id(
+        storage: &mut dyn Storage,
+        token: &mut Token,
+    ) -> Result<(), ContractError> {
+        let mut state = STATE.load(storage)?;
+        token.token_id = state.token_ids.register(&token.token_id)?;
+        STATE.save(storage, &state)?;
+        Ok(())
+    }
+
     pub fn add_token(
         deps: &mut DepsMut,
         env: Env,
-        _info: MessageInfo,
+        info: MessageInfo,
         token: Token,
     ) -> Result<Response, ContractError> {
         if read_state(deps.storage, |s| s.tokens.contains_key(&token.token_id)) {
             return Err(ContractError::TokenAleardyExist);
         }
         let sender = env.contract.address.to_string();
+        let bound_denom = read_state(deps.storage, |s| s.bound_denom(&token.token_id));
+        // the local id is a valid subdenom, long ids were already hashed when normalized
+        let denom = bound_denom
+            .clone()
+            .unwrap_or_else(|| factory_denom(&env.contract.address, &token.token_id));
         STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
             state.tokens.insert(token.token_id.clone(), token.clone());
+            state
+                .token_denoms
+                .insert(token.token_id.clone(), denom.clone());
             Ok(state)
         })?;
+        record_token_change(deps.storage, &env, &info.sender, None, &token)?;
+
+        // escrowed and adopted tokens keep their existing denom, there is nothing to create
+        if bound_denom.is_some() {
+            return Ok(Response::new());
+        }
 
         let msg = MsgCreateDenom {
             sender: sender.clone(),
             subdenom: token.token_id.clone(),
         };
-        let cosmos_msg = CosmosMsg::Stargate {
-            type_url: "/osmosis.tokenfactory.v1beta1.MsgCreateDenom".into(),
-            value: Binary::new(msg.encode_to_vec()),
-        };
+        let cosmos_msg = stargate_msg(
+            "/osmosis.tokenfactory.v1beta1.MsgCreateDenom",
+            msg.encode_to_vec(),
+        );
 
-        let token_base_denom = token_denom(env.contract.address.to_string(), token.token_id);
-        let set_denom_metadata_msg = MsgSetDenomMetadata {
-            sender: sender.clone(),
-            metadata: Some(Metadata {
-                description: token.name.clone(),
-                denom_units: vec![
-                    DenomUnit {
-                        denom: token_base_denom.clone(),
-                        exponent: 0,
-                        aliases: vec![],
-                    },
-                    DenomUnit {
-                        denom: token.symbol.clone(),
-                        exponent: token.decimals as u32,
-                        aliases: vec![],
-                    },
-                ],
-                base: token_base_denom,
-                display: token.symbol.clone(),
-                name: token.name,
-                symbol: token.symbol,
-                uri: token.icon.unwrap_or("".to_string()),
-                uri_hash: "".to_string(),
-            }),
-        };
-        let update_msg = CosmosMsg::Stargate {
-            type_url: "/osmosis.tokenfactory.v1beta1.MsgSetDenomMetadata".to_string(),
-            value: set_denom_metadata_msg.encode_to_vec().into(),
-        };
+        let update_msg = build_set_denom_metadata_msg(&env.contract.address, denom, &token)?;
 
         Ok(Response::new()
             .add_message(cosmos_msg)
             .add_message(update_msg))
     }
 
-    pub fn privilege_mint_token(
-        deps: DepsMut,
-        env: Env,
-        info: MessageInfo,
-        ticket_id: String,
-        token_id: String,
-        receiver: Addr,
-        amount: String,
-        transmuter: Option<String>,
+    /// Stops minting a token and opens its sunset, the token is archived at once when there is
+    /// no sunset period.
+    fn remove_token_directive(
+        api: &dyn Api,
+        storage: &mut dyn Storage,
+        env: &Env,
+        remove_token: RemoveToken,
     ) -> Result<Response, ContractError> {
-        if read_state(deps.storage, |s| {
-            s.route != info.sender && s.admin != info.sender
-        }) {
-            return Err(ContractError::Unauthorized);
-        }
-
-        if read_state(deps.storage, |s| s.handled_tickets.contains(&ticket_id)) {
-            return Err(ContractError::TicketAlreadyHandled);
+        for receiver in [&remove_token.denom_admin, &remove_token.escrow_receiver]
+            .into_iter()
+            .flatten()
+        {
+            api.addr_validate(receiver)?;
         }
-
-        let token = read_state(deps.storage, |s| match s.tokens.get(&token_id) {
-            Some(token) => Ok(token.clone()),
-            None => Err(ContractError::TokenNotFound),
-        })?;
-
-        STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
-            state.handled_tickets.insert(ticket_id.clone());
+        let token_id = read_state(storage, |s| s.token_ids.local_id(&remove_token.token_id));
+        let now = env.block.time.seconds();
+        let sunset = TokenSunset {
+            removed_at: now,
+            redeem_until: now.saturating_add(remove_token.sunset_secs.unwrap_or_default()),
+            denom_admin: remove_token.denom_admin,
+            escrow_receiver: remove_token.escrow_receiver,
+        };
+        STATE.update(storage, |mut state| -> Result<_, ContractError> {
+            if !state.tokens.contains_key(&token_id) {
+                return Err(ContractError::TokenNotFound);
+            }
+            if state.removed_tokens.contains_key(&token_id) {
+                return Err(ContractError::TokenRemoved);
+            }
+            state
+                .removed_tokens
+                .insert(token_id.clone(), sunset.clone());
             Ok(state)
         })?;
 
-        let (ckbtc_token_id, allbtc_token_denom) = read_state(deps.storage, |s| {
-            (s.ckbtc_token_id.clone(), s.allbtc_token_denom.clone())
-        });
-
-        let denom = token_denom(env.contract.address.to_string(), token.token_id);
+        let response = Response::new().add_event(Event::new("TokenRemoved").add_attributes(vec![
+            Attribute::new("token_id", token_id.clone()),
+            Attribute::new("redeem_until", sunset.redeem_until.to_string()),
+        ]));
+        if sunset.redeem_until > now {
+            return Ok(response);
+        }
+        let archived = archive_token(storage, env, token_id)?;
+        Ok(response
+            .add_submessages(archived.messages)
+            .add_events(archived.events))
+    }
 
-        let ckbtc_mint_receiver = if transmuter.is_some() {
-            if token_id.ne(&ckbtc_token_id) || transmuter.clone().unwrap().ne(&allbtc_token_denom) {
-                return Err(ContractError::CustomError(
-                    "Only Support transmuter ckbtc to allBTC".to_string(),
-                ));
-            }
-            env.contract.address.to_string()
-        } else {
-            receiver.to_string()
-        };
+    /// Moves a removed token whose sunset is over into `ARCHIVED_TOKENS`. Its denom admin is
+    /// handed over through a timelocked operation. Coins still escrowed stay reserved in the
+    /// contract, and are released to the receiver the hub named, if any, after the timelock.
+    /// Anyone may call it, the outcome was fixed by the `RemoveToken` directive.
+    pub fn archive_token(
+        storage: &mut dyn Storage,
+        env: &Env,
+        token_id: String,
+    ) -> Result<Response, ContractError> {
+        let mut state = STATE.load(storage)?;
+        let token_id = state.token_ids.local_id(&token_id);
+        let sunset = state
+            .removed_tokens
+            .get(&token_id)
+            .cloned()
+            .ok_or(ContractError::TokenNotFound)?;
+        if !state.is_sunset_over(&token_id, env) {
+            return Err(ContractError::SunsetNotOver(sunset.redeem_until));
+        }
 
-        let msg = MsgMint {
-            sender: env.contract.address.to_string(),
-            amount: Some(Coin {
+        let token = state
+            .tokens
+            .remove(&token_id)
+            .ok_or(ContractError::TokenNotFound)?;
+        let denom = state.token_denom(&token_id)?;
+        let omnity_id = state.token_ids.omnity_id(&token_id);
+        let escrowed = state.escrow_tokens.remove(&token_id).is_some();
+        let escrow_balance = state.escrow_balances.remove(&token_id).unwrap_or_default();
+        // the contract never owned the native denom of an escrowed token
+        let denom_admin = (!escrowed).then(|| {
+            sunset
+                .denom_admin
+                .clone()
+                .unwrap_or_else(|| state.admin.to_string())
+        });
+        if escrow_balance > 0 {
+            state.archived_escrow.insert(
+                token_id.clone(),
+                cosmwasm_std::Coin::new(escrow_balance, denom.clone()),
+            );
+        }
+        state.removed_tokens.remove(&token_id);
+        state.token_denoms.remove(&token_id);
+        state.token_ids.remove(&token_id);
+        state.token_routes.remove(&token_id);
+        state.adopted_denoms.remove(&token_id);
+        state.chain_key_tokens.remove(&token_id);
+        state
+            .target_chain_redeem_min_amount
+            .retain(|(id, _), _| *id != token_id);
+        STATE.save(storage, &state)?;
+
+        ARCHIVED_TOKENS.save(
+            storage,
+            &token_id,
+            &ArchivedToken {
+                token,
+                omnity_id,
                 denom: denom.clone(),
-                amount: amount.clone(),
-            }),
-            mint_to_address: ckbtc_mint_receiver,
-        };
-        let cosmos_msg = CosmosMsg::Stargate {
-            type_url: "/osmosis.tokenfactory.v1beta1.MsgMint".into(),
-            value: Binary::new(msg.encode_to_vec()),
-        };
-
-        // let mint_token_msg = ExecuteMsg::PrivilegeMintToken {
-        //     ticket_id,
-        //     token_id,
-        //     receiver: if transmuter.is_some() {env.contract.address} else { receiver },
-        //     amount,
-        //     transmuter
-        // };
-
-        let mint_token_payload = MintTokenPayload {
-            ticket_id,
-            token_id,
-            receiver,
-            amount,
-            transmuter,
-        };
-
-        Ok(Response::new().add_submessage(
-            SubMsg::reply_on_success(cosmos_msg, reply_msg_id::MINT_TOKEN_REPLY_ID).with_payload(
-                serde_json::to_vec(&mint_token_payload)
-                    .map_err(|e| ContractError::CustomError(e.to_string()))?,
-            ),
-        ))
-
-        // Ok(Response::new().add_message(cosmos_msg).add_event(
-        //     Event::new("TokenMinted").add_attributes(vec![
-        //         Attribute::new("ticket_id", ticket_id),
-        //         Attribute::new("token_id", token_id),
-        //         Attribute::new("receiver", receiver),
-        //         Attribute::new("amount", amount),
-        //     ]),
-        // ))
+                removed_at: sunset.removed_at,
+                archived_at: env.block.time.seconds(),
+                denom_admin: denom_admin.clone(),
+                escrow_balance,
+                escrow_receiver: sunset.escrow_receiver.clone(),
+            },
+        )?;
+
+        let mut response =
+            Response::new().add_event(Event::new("TokenArchived").add_attributes(vec![
+                Attribute::new("token_id", token_id.clone()),
+                Attribute::new("denom", denom.clone()),
+                Attribute::new("denom_admin", denom_admin.clone().unwrap_or_default()),
+            ]));
+        // a directive must not move the denom faster than the admin could
+        if let Some(new_admin) = denom_admin {
+            let scheduled = schedule_operation(
+                storage,
+                env,
+                &env.contract.address,
+                TimelockedAction::TransferDenomAdmin {
+                    token_ids: vec![token_id.clone()],
+                    new_admin,
+                },
+            )?;
+            response = response.add_events(scheduled.events);
+        }
+        if let Some(receiver) = sunset.escrow_receiver.filter(|_| escrow_balance > 0) {
+            let scheduled = schedule_operation(
+                storage,
+                env,
+                &env.contract.address,
+                TimelockedAction::ReleaseArchivedEscrow { token_id, receiver },
+            )?;
+            response = response.add_events(scheduled.events);
+        }
+        Ok(response)
     }
 
-    pub fn redeem_allbtc(
+    /// Schedules handing the denom admin of `token_id`, or of every minted token when none,
+    /// to `new_admin`. Lets a successor port take over the denoms after the timelock.
+    pub fn transfer_denom_admin(
         deps: DepsMut,
         env: Env,
         info: MessageInfo,
-        receiver: String,
-        amount: String,
-        target_chain: String,
+        token_id: Option<String>,
+        new_admin: String,
     ) -> Result<Response, ContractError> {
-        let token_id = read_state(deps.storage, |s| s.ckbtc_token_id.clone());
-        check_target_chain(&deps, target_chain.clone())?;
-        let (fee_token, fee_amount) = check_fee(&deps, &info, target_chain.clone())?;
-        let (allbtc_denom, pool_id) = read_state(deps.storage, |s| {
-            (s.allbtc_token_denom.clone(), s.allbtc_swap_pool_id)
-        });
-        let ckbtc_denom = token_denom(env.contract.address.to_string(), token_id.clone());
-        let allbtc_amount = info
-            .funds
-            .iter()
-            .find(|coin| coin.denom == allbtc_denom)
-            .map(|e| e.amount.u128())
-            .unwrap_or(0);
-        check_min_amount(&deps, &token_id, &target_chain, &allbtc_amount.to_string())?;
+        if read_state(deps.storage, |s| info.sender != s.admin) {
+            return Err(ContractError::Unauthorized);
+        }
+        deps.api.addr_validate(&new_admin)?;
 
-        // swap allbtc to ckbtc
-        let swap_msg = MsgSwapExactAmountIn {
-            sender: env.contract.address.to_string(),
-            routes: vec![
-                osmosis_std::types::osmosis::poolmanager::v1beta1::SwapAmountInRoute {
-                    pool_id: pool_id,
-                    token_out_denom: ckbtc_denom,
-                },
-            ],
-            token_in: Some(osmosis_std::types::cosmos::base::v1beta1::Coin {
-                denom: allbtc_denom,
-                amount: allbtc_amount.to_string(),
-            }),
-            token_out_min_amount: allbtc_amount.to_string(),
-        };
+        let token_ids = read_state(deps.storage, |s| match token_id {
+            Some(token_id) => {
+                let token_id = s.token_ids.local_id(&token_id);
+                if !s.tokens.contains_key(&token_id) {
+                    return Err(ContractError::TokenNotFound);
+                }
+                // the contract never owned the native denom of an escrowed token
+                if s.escrow_tokens.contains_key(&token_id) {
+                    return Err(ContractError::TokenNotMinted);
+                }
+                Ok(vec![token_id])
+            }
+            None => Ok(s
+                .tokens
+                .keys()
+                .filter(|token_id| !s.escrow_tokens.contains_key(*token_id))
+                .cloned()
+                .collect()),
+        })?;
 
-        let cosmos_msg = CosmosMsg::Stargate {
-            type_url: "/osmosis.poolmanager.v1beta1.MsgSwapExactAmountIn".into(),
-            value: Binary::new(swap_msg.to_proto_bytes()),
-        };
+        schedule_operation(
+            deps.storage,
+            &env,
+            &info.sender,
+            TimelockedAction::TransferDenomAdmin {
+                token_ids,
+                new_admin,
+            },
+        )
+    }
 
-        let redeem_allbtc = RedeemAllBTC {
-            sender: info.sender.into_string(),
-            receiver,
-            amount,
-            target_chain,
-            fee_token,
-            fee_amount: fee_amount.to_string(),
+    fn schedule_operation(
+        storage: &mut dyn Storage,
+        env: &Env,
+        scheduled_by: &Addr,
+        action: TimelockedAction,
+    ) -> Result<Response, ContractError> {
+        let mut state = STATE.load(storage)?;
+        let id = state.operation_sequence;
+        state.operation_sequence += 1;
+        STATE.save(storage, &state)?;
+
+        let scheduled_at = env.block.time.seconds();
+        let operation = PendingOperation {
+            id,
+            action,
+            scheduled_by: scheduled_by.clone(),
+            scheduled_at,
+            executable_at: scheduled_at.saturating_add(state.timelock_delay()),
         };
+        PENDING_OPERATIONS.save(storage, id, &operation)?;
 
-        Ok(Response::new().add_submessage(
-            SubMsg::reply_always(cosmos_msg, reply_msg_id::SWAP_ALLBTC_TO_CKBTC_REPLY_ID)
-                .with_payload(
-                    serde_json::to_vec(&redeem_allbtc)
+        Ok(
+            Response::new().add_event(Event::new("OperationScheduled").add_attributes(vec![
+                Attribute::new("id", id.to_string()),
+                Attribute::new(
+                    "action",
+                    serde_json::to_string(&operation.action)
                         .map_err(|e| ContractError::CustomError(e.to_string()))?,
                 ),
-        ))
+                Attribute::new("executable_at", operation.executable_at.to_string()),
+            ])),
+        )
     }
 
-    pub fn redeem_token(
+    /// Runs a scheduled operation once its timelock has passed, on behalf of the admin or of
+    /// whoever scheduled it.
+    pub fn execute_operation(
         deps: DepsMut,
         env: Env,
         info: MessageInfo,
-        mut token_id: String,
-        receiver: String,
-        amount: String,
-        target_chain: String,
+        id: u64,
     ) -> Result<Response, ContractError> {
-        token_id = token_id.replace("•", ".");
-
-        let token = read_state(deps.storage, |s| match s.tokens.get(&token_id) {
-            Some(token) => Ok(token.clone()),
-            None => Err(ContractError::TokenNotFound),
-        })?;
-
-        check_target_chain(&deps, target_chain.clone())?;
-        let (fee_token, fee_amount) = check_fee(&deps, &info, target_chain.clone())?;
-        check_min_amount(&deps, &token_id, &target_chain, &amount)?;
-
-        let denom = token_denom(env.contract.address.to_string(), token.token_id);
+        let operation = PENDING_OPERATIONS
+            .may_load(deps.storage, id)?
+            .ok_or(ContractError::OperationNotFound)?;
+        if read_state(deps.storage, |s| {
+            info.sender != s.admin && info.sender != operation.scheduled_by
+        }) {
+            return Err(ContractError::Unauthorized);
+        }
+        if env.block.time.seconds() < operation.executable_at {
+            return Err(ContractError::OperationLocked(operation.executable_at));
+        }
+        PENDING_OPERATIONS.remove(deps.storage, id);
 
-        let burn_msg = build_burn_msg(
-            env.contract.address,
-            info.sender.clone(),
-            denom,
-            amount.clone(),
+        let mut response = Response::new().add_event(
+            Event::new("OperationExecuted")
+                .add_attributes(vec![Attribute::new("id", id.to_string())]),
         );
+        match operation.action {
+            TimelockedAction::TransferDenomAdmin {
+                token_ids,
+                new_admin,
+            } => {
+                for token_id in token_ids {
+                    // the handoff of an archived token is scheduled once it left the state
+                    let denom = if read_state(deps.storage, |s| s.tokens.contains_key(&token_id)) {
+                        read_state(deps.storage, |s| s.token_denom(&token_id))?
+                    } else {
+                        ARCHIVED_TOKENS.load(deps.storage, &token_id)?.denom
+                    };
+                    let msg = MsgChangeAdmin {
+                        sender: env.contract.address.to_string(),
+                        denom: denom.clone(),
+                        new_admin: new_admin.clone(),
+                    };
+                    response = response
+                        .add_message(stargate_msg(
+                            "/osmosis.tokenfactory.v1beta1.MsgChangeAdmin",
+                            msg.encode_to_vec(),
+                        ))
+                        .add_event(Event::new("DenomAdminTransferred").add_attributes(vec![
+                            Attribute::new("token_id", token_id),
+                            Attribute::new("denom", denom),
+                            Attribute::new("new_admin", new_admin.clone()),
+                        ]));
+                }
+            }
+            TimelockedAction::UpdateRoute { route } => {
+                STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+                    state.route = route.clone();
+                    Ok(state)
+                })?;
+                response = response.add_event(
+                    Event::new("RouteUpdated")
+                        .add_attributes(vec![Attribute::new("new_route", route)]),
+                );
+            }
+            TimelockedAction::RefundToken {
+                denom,
+                receiver,
+                amount,
+            } => {
+                // the reserved coins may have grown since the refund was scheduled
+                check_refundable(deps.as_ref(), &env, &denom, &amount)?;
+                response =
+                    response.add_message(build_contract_transfer_msg(denom, amount, receiver)?);
+            }
+            TimelockedAction::ReleaseArchivedEscrow { token_id, receiver } => {
+                let mut state = STATE.load(deps.storage)?;
+                let coin = state
+                    .archived_escrow
+                    .remove(&token_id)
+                    .ok_or(ContractError::TokenNotFound)?;
+                STATE.save(deps.storage, &state)?;
+                response = response
+                    .add_message(BankMsg::Send {
+                        to_address: receiver.clone(),
+                        amount: vec![coin.clone()],
+                    })
+                    .add_event(Event::new("ArchivedEscrowReleased").add_attributes(vec![
+                        Attribute::new("token_id", token_id),
+                        Attribute::new("receiver", receiver),
+                        Attribute::new("amount", coin.to_string()),
+                    ]));
+            }
+            TimelockedAction::SetRelayReward { reward } => {
+                STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+                    state.relay_reward = reward;
+                    Ok(state)
+                })?;
+                response =
+                    response.add_event(Event::new("RelayRewardUpdated").add_attributes(vec![
+                        Attribute::new("reward", reward.unwrap_or_default().to_string()),
+                    ]));
+            }
+            TimelockedAction::SetIbcPort { port } => {
+                let channel = STATE.load(deps.storage)?.ibc_channel;
+                STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+                    state.ibc_port = port.clone();
+                    state.ibc_channel = None;
+                    Ok(state)
+                })?;
+                if let Some(channel_id) = channel {
+                    response = response.add_message(IbcMsg::CloseChannel { channel_id });
+                }
+                let port = port.unwrap_or(CounterpartPort {
+                    connection_id: String::new(),
+                    port_id: String::new(),
+                });
+                response = response.add_event(Event::new("IbcPortUpdated").add_attributes(vec![
+                    Attribute::new("connection_id", port.connection_id),
+                    Attribute::new("port_id", port.port_id),
+                ]));
+            }
+            TimelockedAction::SetAttestors { attestors } => {
+                STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+                    state.attestors = attestors.clone();
+                    Ok(state)
+                })?;
+                response = response.add_event(Event::new("AttestorsUpdated").add_attributes(
+                    vec![Attribute::new(
+                        "threshold",
+                        attestors
+                            .map(|attestors| attestors.threshold.to_string())
+                            .unwrap_or_default(),
+                    )],
+                ));
+            }
+            TimelockedAction::SetHubKey { hub_key } => {
+                STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+                    state.hub_key = hub_key.clone();
+                    Ok(state)
+                })?;
+                response = response.add_event(Event::new("HubKeyUpdated").add_attributes(vec![
+                    Attribute::new(
+                        "public_key",
+                        hub_key
+                            .map(|hub_key| hub_key.public_key.to_base64())
+                            .unwrap_or_default(),
+                    ),
+                ]));
+            }
+            TimelockedAction::SetTimelockDelay { delay_secs } => {
+                let state =
+                    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+                        state.timelock_delay = delay_secs;
+                        Ok(state)
+                    })?;
+                response =
+                    response.add_event(Event::new("TimelockDelayUpdated").add_attributes(vec![
+                        Attribute::new("delay_secs", state.timelock_delay().to_string()),
+                    ]));
+            }
+        }
+        Ok(response)
+    }
 
-        let mut state = STATE.load(deps.storage).expect("State not initialized!");
-        let current_seq = state.generate_ticket_sequence;
-        state.generate_ticket_sequence += 1;
-        STATE.save(deps.storage, &state)?;
-
-        let req = GenerateTicketReq {
-            seq: current_seq,
-            target_chain_id: target_chain,
-            sender: info.sender.into_string(),
-            receiver,
-            token_id: state.replace_token_id_if_runes(&token_id),
-            amount: amount.clone(),
-            action: crate::state::TxAction::RedeemIcpChainKeyAssets(IcpChainKeyToken::CKBTC),
-            timestamp: env.block.time.nanos(),
-            block_height: env.block.height,
-            memo: None,
-            fee_token,
-            fee_amount: fee_amount.to_string(),
-        };
-
-        Ok(Response::new().add_submessage(
-            SubMsg::reply_on_success(burn_msg, reply_msg_id::REDEEM_REPLY_ID).with_payload(
-                serde_json::to_vec(&req).map_err(|e| ContractError::CustomError(e.to_string()))?,
-            ),
+    pub fn cancel_operation(
+        deps: DepsMut,
+        info: MessageInfo,
+        id: u64,
+    ) -> Result<Response, ContractError> {
+        if read_state(deps.storage, |s| info.sender != s.admin) {
+            return Err(ContractError::Unauthorized);
+        }
+        if !PENDING_OPERATIONS.has(deps.storage, id) {
+            return Err(ContractError::OperationNotFound);
+        }
+        PENDING_OPERATIONS.remove(deps.storage, id);
+
+        Ok(Response::new().add_event(
+            Event::new("OperationCancelled")
+                .add_attributes(vec![Attribute::new("id", id.to_string())]),
         ))
     }
 
-    pub fn generate_ticket(
+    /// Adds the attestor of `attestation` to the matching partial attestation, restarting it
+    /// when it expired, and returns how many attestors agree. Votes of attestors no longer in
+    /// `attestors` are dropped, so a rotated out attestor can't help reach the threshold.
+    /// Every partial attestation of the ticket is dropped once the threshold is reached.
+    fn attest_mint(
+        storage: &mut dyn Storage,
+        env: &Env,
+        attestors: &AttestorSet,
+        attestation: MintAttestation,
+    ) -> Result<u32, ContractError> {
+        let ticket_id = attestation.ticket_id.clone();
+        let digest = attestation.digest();
+        let key = (ticket_id.as_str(), digest.as_str());
+        let mut attestation = match MINT_ATTESTATIONS.may_load(storage, key)? {
+            Some(mut existing) if !existing.is_expired(env) => {
+                existing.attestors.extend(attestation.attestors);
+                existing
+            }
+            _ => attestation,
+        };
+        attestation
+            .attestors
+            .retain(|attestor| attestors.attestors.contains(attestor));
+        let attested = attestation.attestors.len() as u32;
+        if attested < attestors.threshold {
+            MINT_ATTESTATIONS.save(storage, key, &attestation)?;
+            return Ok(attested);
+        }
+
+        let digests = MINT_ATTESTATIONS
+            .prefix(&ticket_id)
+            .keys(storage, None, None, Order::Ascending)
+            .collect::<Result<Vec<_>, _>>()?;
+        for digest in digests {
+            MINT_ATTESTATIONS.remove(storage, (&ticket_id, &digest));
+        }
+        Ok(attested)
+    }
+
+    /// Verifies the hub signature of a mint ticket, its `token_id` is the id the hub signed.
+    pub fn check_hub_signature(
+        deps: Deps,
+        env: &Env,
+        signature: &Binary,
+        ticket: &MintTicket,
+    ) -> Result<(), ContractError> {
+        let (hub_key, chain_id) =
+            read_state(deps.storage, |s| (s.hub_key.clone(), s.chain_id.clone()));
+        let hub_key = hub_key.ok_or(ContractError::InvalidSignature(
+            "no hub key is registered".to_string(),
+        ))?;
+        let message = mint_ticket_message(&env.contract.address, &chain_id, ticket);
+        hub_key.verify(deps.api, &message, signature)
+    }
+
+    pub fn privilege_mint_token(
         deps: DepsMut,
         env: Env,
         info: MessageInfo,
-        mut token_id: String,
-        sender: String,
-        receiver: String,
-        amount: String,
-        target_chain: String,
-        action: crate::state::TxAction,
-        memo: Option<String>,
+        ticket: MintTicket,
+        signature: Option<Binary>,
     ) -> Result<Response, ContractError> {
-        token_id = token_id.replace("•", ".");
-        let token = read_state(deps.storage, |s| match s.tokens.get(&token_id) {
-            Some(token) => Ok(token.clone()),
-            None => Err(ContractError::TokenNotFound),
+        // a ticket signed by the hub needs no trusted sender
+        let attestors = match &signature {
+            Some(signature) => {
+                check_hub_signature(deps.as_ref(), &env, signature, &ticket)?;
+                None
+            }
+            None => {
+                let attestors = read_state(deps.storage, |s| s.attestors.clone());
+                if read_state(deps.storage, |s| match &attestors {
+                    Some(attestors) => !attestors.attestors.contains(&info.sender),
+                    None => s.route != info.sender,
+                }) {
+                    return Err(ContractError::Unauthorized);
+                }
+                attestors
+            }
+        };
+        let relayed = signature.is_some();
+        mint_ticket(deps, env, info.sender, ticket, attestors, relayed)
+    }
+
+    /// Rejects a mint ticket that can't be minted, before anything of it is recorded.
+    /// Removed tokens are no longer minted, escrowed ones are still released until their
+    /// sunset ends as the locked coins back tokens held on other chains.
+    pub fn check_mint(deps: Deps, env: &Env, ticket: &MintTicket) -> Result<(), ContractError> {
+        let amount = parse_amount(&ticket.amount)?;
+        read_state(deps.storage, |s| {
+            let token_id = s.token_ids.local_id(&ticket.token_id);
+            if s.handled_tickets.contains(&ticket.ticket_id) {
+                return Err(ContractError::TicketAlreadyHandled);
+            }
+            if !s.tokens.contains_key(&token_id) {
+                return Err(ContractError::TokenNotFound);
+            }
+            let escrowed = s.escrow_tokens.contains_key(&token_id);
+            if s.removed_tokens.contains_key(&token_id)
+                && (!escrowed || s.is_sunset_over(&token_id, env))
+            {
+                return Err(ContractError::TokenRemoved);
+            }
+            match &ticket.channel {
+                Some(_) => {
+                    if let Some(recovery) = &ticket.recovery {
+                        deps.api.addr_validate(recovery.as_str())?;
+                    }
+                    forward_beneficiary(
+                        &env.contract.address,
+                        ticket.receiver.as_str(),
+                        ticket.recovery.as_ref(),
+                    )?;
+                }
+                None if ticket.recovery.is_some() => {
+                    return Err(ContractError::CustomError(
+                        "Only forwarded tickets take a recovery account".to_string(),
+                    ));
+                }
+                None => {}
+            }
+            if let Some(transmuter) = &ticket.transmuter {
+                if ticket.channel.is_some() {
+                    return Err(ContractError::CustomError(
+                        "Forwarded tokens do not support transmuter".to_string(),
+                    ));
+                }
+                if escrowed {
+                    return Err(ContractError::CustomError(
+                        "Escrowed tokens do not support transmuter".to_string(),
+                    ));
+                }
+                if token_id != s.ckbtc_token_id || *transmuter != s.allbtc_token_denom {
+                    return Err(ContractError::CustomError(
+                        "Only Support transmuter ckbtc to allBTC".to_string(),
+                    ));
+                }
+            }
+            let locked = s
+                .escrow_balances
+                .get(&token_id)
+                .cloned()
+                .unwrap_or_default();
+            if escrowed && locked < amount {
+                return Err(ContractError::InsufficientEscrow(locked, amount));
+            }
+            Ok(())
+        })
+    }
+
+    /// Mints an authorized ticket, or records one more attestation of it while `attestors`
+    /// are below their threshold. `relayed` tickets earn `minter` the relay reward. With a
+    /// `channel` the tokens are minted to the contract and forwarded to `receiver` over it,
+    /// a failed forward is credited to the `recovery` account or the receiver's local one.
+    pub fn mint_ticket(
+        mut deps: DepsMut,
+        env: Env,
+        minter: Addr,
+        ticket: MintTicket,
+        attestors: Option<AttestorSet>,
+        relayed: bool,
+    ) -> Result<Response, ContractError> {
+        check_mint(deps.as_ref(), &env, &ticket)?;
+        let MintTicket {
+            ticket_id,
+            token_id,
+            receiver,
+            amount,
+            transmuter,
+            channel,
+            recovery,
+        } = ticket;
+        let token_id = read_state(deps.storage, |s| s.token_ids.local_id(&token_id));
+
+        if let Some(attestors) = attestors {
+            let attestation = MintAttestation {
+                ticket_id: ticket_id.clone(),
+                token_id: token_id.clone(),
+                receiver: receiver.clone(),
+                amount: amount.clone(),
+                transmuter: transmuter.clone(),
+                channel: channel.clone(),
+                recovery: recovery.clone(),
+                attestors: BTreeSet::from([minter.clone()]),
+                expires_at: env.block.time.seconds() + attestors.expiry_secs,
+            };
+            let attested = attest_mint(deps.storage, &env, &attestors, attestation)?;
+            if attested < attestors.threshold {
+                return Ok(
+                    Response::new().add_event(Event::new("MintAttested").add_attributes(vec![
+                        Attribute::new("ticket_id", ticket_id),
+                        Attribute::new("attestor", minter),
+                        Attribute::new("attestations", attested.to_string()),
+                        Attribute::new("threshold", attestors.threshold.to_string()),
+                    ])),
+                );
+            }
+        }
+
+        STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+            state.handled_tickets.insert(ticket_id.clone());
+            Ok(state)
         })?;
+        // paid before the release below, so the balance still counts the released coins
+        let reward = match relayed {
+            true => reward_relayer(deps.branch(), &env, &minter)?,
+            false => Response::new(),
+        };
+
+        let escrow_denom = read_state(deps.storage, |s| s.escrow_tokens.get(&token_id).cloned());
+        if let Some(channel) = channel {
+            let beneficiary =
+                forward_beneficiary(&env.contract.address, receiver.as_str(), recovery.as_ref())?;
+            let amount_u128 = parse_amount(&amount)?;
+            let (response, denom) = match escrow_denom {
+                Some(denom) => {
+                    unlock_escrow(deps.storage, &token_id, amount_u128)?;
+                    (Response::new(), denom)
+                }
+                None => {
+                    let denom = read_state(deps.storage, |s| s.token_denom(&token_id))?;
+                    let mint_msg = build_mint_msg(
+                        env.contract.address.clone(),
+                        env.contract.address.to_string(),
+                        denom.clone(),
+                        amount,
+                    );
+                    (Response::new().add_message(mint_msg), denom)
+                }
+            };
+            let forward = Forward {
+                ticket_id,
+                channel,
+                receiver: receiver.to_string(),
+                beneficiary,
+                amount: cosmwasm_std::Coin::new(amount_u128, denom),
+            };
+            return Ok(response
+                .add_submessage(forward_msg(&env, &forward)?)
+                .add_submessages(reward.messages)
+                .add_events(reward.events));
+        }
+        if let Some(escrow_denom) = escrow_denom {
+            let response =
+                release_escrow(deps, ticket_id, token_id, receiver, escrow_denom, amount)?;
+            return Ok(response
+                .add_submessages(reward.messages)
+                .add_events(reward.events));
+        }
 
-        check_target_chain(&deps, target_chain.clone())?;
-        let (fee_token, fee_amount) = check_fee(&deps, &info, target_chain.clone())?;
-        check_min_amount(&deps, &token_id, &target_chain, &amount)?;
+        let denom = read_state(deps.storage, |s| s.token_denom(&token_id))?;
 
-        let denom = token_denom(env.contract.address.to_string(), token.token_id);
+        let ckbtc_mint_receiver = if transmuter.is_some() {
+            env.contract.address.to_string()
+        } else {
+            receiver.to_string()
+        };
 
-        let burn_msg = build_burn_msg(
-            env.contract.address,
-            info.sender.clone(),
+        let cosmos_msg = build_mint_msg(
+            env.contract.address.clone(),
+            ckbtc_mint_receiver,
             denom,
             amount.clone(),
         );
 
-        let mut state = STATE.load(deps.storage).expect("State not initialized!");
-        let current_seq = state.generate_ticket_sequence;
-        state.generate_ticket_sequence += 1;
-        STATE.save(deps.storage, &state)?;
+        // let mint_token_msg = ExecuteMsg::PrivilegeMintToken {
+        //     ticket_id,
+        //     token_id,
+        //     receiver: if transmuter.is_some() {env.contract.address} else { receiver },
+        //     amount,
+        //     transmuter
+        // };
 
-        let generate_ticket_req = GenerateTicketReq {
-            seq: current_seq,
-            target_chain_id: target_chain,
-            sender,
+        let mint_token_payload = MintTokenPayload {
+            ticket_id,
+            token_id,
             receiver,
-            token_id: state.replace_token_id_if_runes(&token_id),
             amount,
-            action,
-            timestamp: env.block.time.nanos(),
-            block_height: env.block.height,
-            memo,
-            fee_token,
-            fee_amount: fee_amount.to_string(),
+            transmuter,
         };
 
+        Ok(Response::new()
+            .add_submessage(
+                SubMsg::reply_on_success(cosmos_msg, reply_msg_id::MINT_TOKEN_REPLY_ID)
+                    .with_payload(
+                        serde_json::to_vec(&mint_token_payload)
+                            .map_err(|e| ContractError::CustomError(e.to_string()))?,
+                    ),
+            )
+            .add_submessages(reward.messages)
+            .add_events(reward.events))
+
+        // Ok(Response::new().add_message(cosmos_msg).add_event(
+        //     Event::new("TokenMinted").add_attributes(vec![
+        //         Attribute::new("ticket_id", ticket_id),
+        //         Attribute::new("token_id", token_id),
+        //         Attribute::new("receiver", receiver),
+        //         Attribute::new("amount", amount),
+        //     ]),
+        // ))
+    }
+
+    pub fn redeem_allbtc(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        receiver: String,
+        amount: String,
+        target_chain: String,
+    ) -> Result<Response, ContractError> {
+        let (swap_msg, redeem_allbtc) =
+            prepare_redeem_allbtc(deps.as_ref(), &env, &info, receiver, amount, target_chain)?;
+
         Ok(Response::new().add_submessage(
-            SubMsg::reply_on_success(burn_msg, reply_msg_id::GENERATE_TICKET_REPLY_ID)
+            SubMsg::reply_always(swap_msg, reply_msg_id::SWAP_ALLBTC_TO_CKBTC_REPLY_ID)
                 .with_payload(
-                    serde_json::to_vec(&generate_ticket_req)
+                    serde_json::to_vec(&redeem_allbtc)
                         .map_err(|e| ContractError::CustomError(e.to_string()))?,
                 ),
         ))
     }
 
-    pub fn update_route(
+    pub fn redeem_token(
         deps: DepsMut,
+        env: Env,
         info: MessageInfo,
-        route: Addr,
+        token_id: String,
+        receiver: String,
+        amount: String,
+        target_chain: String,
     ) -> Result<Response, ContractError> {
-        if read_state(deps.storage, |s| info.sender != s.admin) {
-            return Err(ContractError::Unauthorized);
+        let (burn_msg, req) = prepare_redeem_token(
+            deps.as_ref(),
+            &env,
+            &info,
+            token_id,
+            receiver,
+            amount,
+            target_chain,
+        )?;
+        next_ticket_seq(deps.storage)?;
+
+        submit_ticket(deps, &env, Response::new(), burn_msg, req, true)
+    }
+
+    pub fn generate_ticket(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        params: TicketParams,
+    ) -> Result<Response, ContractError> {
+        let (burn_msg, generate_ticket_req) =
+            prepare_generate_ticket(deps.as_ref(), &env, &info, params)?;
+        next_ticket_seq(deps.storage)?;
+
+        let mut response = Response::new();
+        if let Some(granter) = &generate_ticket_req.on_behalf_of {
+            response = response.add_event(Event::new("OnBehalfOf").add_attributes(vec![
+                Attribute::new("granter", granter.clone()),
+                Attribute::new("operator", info.sender.clone()),
+                Attribute::new("seq", generate_ticket_req.seq.to_string()),
+            ]));
         }
 
-        STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
-            state.route = route.clone();
-            Ok(state)
-        })?;
+        submit_ticket(deps, &env, response, burn_msg, generate_ticket_req, false)
+    }
 
-        Ok(Response::new().add_event(
-            Event::new("RouteUpdated").add_attributes(vec![Attribute::new("new_route", route)]),
+    /// Sends out a prepared ticket. Minted tokens are burned first and the ticket is recorded in
+    /// the reply, escrowed coins are locked right away.
+    fn submit_ticket(
+        deps: DepsMut,
+        env: &Env,
+        response: Response,
+        burn_msg: Option<CosmosMsg>,
+        req: GenerateTicketReq,
+        redeem: bool,
+    ) -> Result<Response, ContractError> {
+        match burn_msg {
+            Some(burn_msg) => {
+                let reply_id = match redeem {
+                    true => reply_msg_id::REDEEM_REPLY_ID,
+                    false => reply_msg_id::GENERATE_TICKET_REPLY_ID,
+                };
+                Ok(response.add_submessage(
+                    SubMsg::reply_on_success(burn_msg, reply_id).with_payload(
+                        serde_json::to_vec(&req)
+                            .map_err(|e| ContractError::CustomError(e.to_string()))?,
+                    ),
+                ))
+            }
+            None => {
+                let lock_event = lock_escrow(deps.storage, &req)?;
+                let mut response = response.add_event(lock_event);
+                if redeem {
+                    response = response.add_event(redeem_requested_event(&req));
+                }
+                deliver_ticket(deps.storage, env, response, req)
+            }
+        }
+    }
+
+    /// Entry of an ICS-20 transfer with a wasm memo. Only ibc-hooks can send from the
+    /// intermediate sender of `original_sender`, which authenticates the ticket's sender. The
+    /// ticket runs in a call to the contract itself, so a failing ticket is rolled back and the
+    /// transferred funds are sent to `recovery` instead of failing the transfer.
+    pub fn ibc_hook(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        channel: String,
+        original_sender: String,
+        recovery: String,
+        action: HookAction,
+    ) -> Result<Response, ContractError> {
+        if intermediate_sender(&env.contract.address, &channel, &original_sender)?
+            != info.sender.as_str()
+        {
+            return Err(ContractError::Unauthorized);
+        }
+        let recovery = deps.api.addr_validate(&recovery)?;
+
+        let hook_msg = WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            msg: to_json_binary(&ExecuteMsg::ExecuteHook {
+                original_sender: original_sender.clone(),
+                recovery: recovery.clone(),
+                action,
+            })?,
+            funds: info.funds.clone(),
+        };
+        let recovery = HookRecovery {
+            channel,
+            original_sender,
+            recovery,
+            funds: info.funds,
+        };
+        Ok(Response::new().add_submessage(
+            SubMsg::reply_on_error(hook_msg, reply_msg_id::IBC_HOOK_REPLY_ID).with_payload(
+                serde_json::to_vec(&recovery)
+                    .map_err(|e| ContractError::CustomError(e.to_string()))?,
+            ),
         ))
     }
 
-    pub fn redeem_setting(
+    /// Ticket of an ibc hook, sent by the contract with the transferred funds. Minted tokens
+    /// arrive with the transfer and are burned from the contract. The fee is paid out of the
+    /// transferred coin, and the ticket is refunded to `recovery` if it never reaches the hub.
+    pub fn execute_hook(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        original_sender: String,
+        recovery: Addr,
+        action: HookAction,
+    ) -> Result<Response, ContractError> {
+        if info.sender != env.contract.address {
+            return Err(ContractError::Unauthorized);
+        }
+
+        let (token_id, amount, redeem, (burn_msg, mut req)) = match action {
+            HookAction::GenerateTicket {
+                token_id,
+                receiver,
+                amount,
+                target_chain,
+                action,
+                memo,
+            } => {
+                let token_id = read_state(deps.storage, |s| s.token_ids.local_id(&token_id));
+                let prepared = prepare_ticket(
+                    deps.as_ref(),
+                    &env,
+                    &info,
+                    TicketParams {
+                        token_id: token_id.clone(),
+                        sender: original_sender,
+                        receiver,
+                        amount: amount.clone(),
+                        target_chain,
+                        action,
+                        memo,
+                        from_hook: true,
+                    },
+                )?;
+                (token_id, amount, false, prepared)
+            }
+            HookAction::RedeemToken {
+                token_id,
+                receiver,
+                amount,
+                target_chain,
+            } => {
+                let token_id = read_state(deps.storage, |s| s.token_ids.local_id(&token_id));
+                let action =
+                    read_state(deps.storage, |s| s.redeem_action(&token_id, &target_chain));
+                let prepared = prepare_ticket(
+                    deps.as_ref(),
+                    &env,
+                    &info,
+                    TicketParams {
+                        token_id: token_id.clone(),
+                        sender: original_sender,
+                        receiver,
+                        amount: amount.clone(),
+                        target_chain,
+                        action,
+                        memo: None,
+                        from_hook: true,
+                    },
+                )?;
+                (token_id, amount, true, prepared)
+            }
+        };
+        // escrowed coins were checked against the funds when the ticket was prepared
+        if burn_msg.is_some() {
+            let denom = read_state(deps.storage, |s| s.token_denom(&token_id))?;
+            check_locked_funds(
+                &info,
+                &cosmwasm_std::Coin::new(parse_amount(&amount)?, denom),
+                &req.fee_token,
+                parse_amount(&req.fee_amount)?,
+            )?;
+        }
+        req.refund_to = Some(recovery.to_string());
+        next_ticket_seq(deps.storage)?;
+
+        submit_ticket(deps, &env, Response::new(), burn_msg, req, redeem)
+    }
+
+    /// Validates a `RedeemAllBTC` call and builds the allBTC -> ckBTC swap that starts it.
+    /// The ticket itself is only built once the swap replies, see `redeem_allbtc_ticket_req`.
+    pub fn prepare_redeem_allbtc(
+        deps: Deps,
+        env: &Env,
+        info: &MessageInfo,
+        receiver: String,
+        amount: String,
+        target_chain: String,
+    ) -> Result<(CosmosMsg, RedeemAllBTC), ContractError> {
+        let token_id = read_state(deps.storage, |s| s.ckbtc_token_id.clone());
+        check_target_chain(deps, target_chain.clone())?;
+        check_token_route(deps, &token_id, &target_chain)?;
+        check_receiver(deps, &target_chain, &receiver)?;
+        let (fee_token, fee_amount) = check_fee(deps, info, target_chain.clone(), None)?;
+        let (allbtc_denom, pool_id) = read_state(deps.storage, |s| {
+            (s.allbtc_token_denom.clone(), s.allbtc_swap_pool_id)
+        });
+        let ckbtc_denom = read_state(deps.storage, |s| s.token_denom(&token_id))?;
+        let allbtc_amount = info
+            .funds
+            .iter()
+            .find(|coin| coin.denom == allbtc_denom)
+            .map(|e| e.amount.u128())
+            .unwrap_or(0);
+        check_min_amount(deps, &token_id, &target_chain, &allbtc_amount.to_string())?;
+
+        // swap allbtc to ckbtc
+        let swap_msg = MsgSwapExactAmountIn {
+            sender: env.contract.address.to_string(),
+            routes: vec![
+                osmosis_std::types::osmosis::poolmanager::v1beta1::SwapAmountInRoute {
+                    pool_id,
+                    token_out_denom: ckbtc_denom,
+                },
+            ],
+            token_in: Some(osmosis_std::types::cosmos::base::v1beta1::Coin {
+                denom: allbtc_denom,
+                amount: allbtc_amount.to_string(),
+            }),
+            token_out_min_amount: allbtc_amount.to_string(),
+        };
+
+        let cosmos_msg = stargate_msg(
+            "/osmosis.poolmanager.v1beta1.MsgSwapExactAmountIn",
+            swap_msg.to_proto_bytes(),
+        );
+
+        let redeem_allbtc = RedeemAllBTC {
+            sender: info.sender.to_string(),
+            receiver,
+            amount,
+            target_chain,
+            fee_token,
+            fee_amount: fee_amount.to_string(),
+        };
+
+        Ok((cosmos_msg, redeem_allbtc))
+    }
+
+    /// Builds the ckBTC ticket emitted once the allBTC of a `RedeemAllBTC` call has been swapped.
+    pub fn redeem_allbtc_ticket_req(
+        storage: &dyn Storage,
+        env: &Env,
+        seq: u64,
+        redeem_allbtc: RedeemAllBTC,
+    ) -> GenerateTicketReq {
+        let (token_id, action) = read_state(storage, |s| {
+            (
+                s.token_ids.omnity_id(&s.ckbtc_token_id),
+                s.redeem_action(&s.ckbtc_token_id, &redeem_allbtc.target_chain),
+            )
+        });
+        GenerateTicketReq {
+            seq,
+            target_chain_id: redeem_allbtc.target_chain,
+            sender: redeem_allbtc.sender,
+            receiver: redeem_allbtc.receiver,
+            token_id,
+            amount: redeem_allbtc.amount,
+            action,
+            timestamp: env.block.time.nanos(),
+            block_height: env.block.height,
+            memo: None,
+            fee_token: redeem_allbtc.fee_token,
+            fee_amount: redeem_allbtc.fee_amount,
+            on_behalf_of: None,
+            refund_to: None,
+        }
+    }
+
+    /// Validates a `RedeemToken` call and builds the burn message and the ticket it produces.
+    /// Nothing is written, so the same code backs `QueryMsg::SimulateRedeem`.
+    pub fn prepare_redeem_token(
+        deps: Deps,
+        env: &Env,
+        info: &MessageInfo,
+        token_id: String,
+        receiver: String,
+        amount: String,
+        target_chain: String,
+    ) -> Result<(Option<CosmosMsg>, GenerateTicketReq), ContractError> {
+        let token_id = read_state(deps.storage, |s| s.token_ids.local_id(&token_id));
+        let action = read_state(deps.storage, |s| s.redeem_action(&token_id, &target_chain));
+        prepare_ticket(
+            deps,
+            env,
+            info,
+            TicketParams {
+                token_id,
+                sender: info.sender.to_string(),
+                receiver,
+                amount,
+                target_chain,
+                action,
+                memo: None,
+                from_hook: false,
+            },
+        )
+    }
+
+    /// Validates a `GenerateTicket` call and builds the burn message and the ticket it produces.
+    /// Nothing is written, so the same code backs `QueryMsg::SimulateGenerateTicket`.
+    pub fn prepare_generate_ticket(
+        deps: Deps,
+        env: &Env,
+        info: &MessageInfo,
+        params: TicketParams,
+    ) -> Result<(Option<CosmosMsg>, GenerateTicketReq), ContractError> {
+        let sender = params.sender.clone();
+        check_sender(deps, env, info, &sender)?;
+        let (burn_msg, mut req) = prepare_ticket(
+            deps,
+            env,
+            info,
+            TicketParams {
+                token_id: read_state(deps.storage, |s| s.token_ids.local_id(&params.token_id)),
+                sender: info.sender.to_string(),
+                from_hook: false,
+                ..params
+            },
+        )?;
+        // the operator's coins are burnt or locked, the granter is only recorded
+        req.on_behalf_of = (sender != info.sender.as_str()).then_some(sender);
+        Ok((burn_msg, req))
+    }
+
+    /// The ticket sender must be the signer, or have approved the signer to act on its behalf.
+    pub fn check_sender(
+        deps: Deps,
+        env: &Env,
+        info: &MessageInfo,
+        sender: &str,
+    ) -> Result<(), ContractError> {
+        if info.sender.as_str() == sender {
+            return Ok(());
+        }
+        let sender = deps
+            .api
+            .addr_validate(sender)
+            .map_err(|_| ContractError::SenderNotApproved)?;
+        match APPROVALS.may_load(deps.storage, (&sender, &info.sender))? {
+            Some(approval) if !approval.is_expired(env) => Ok(()),
+            _ => Err(ContractError::SenderNotApproved),
+        }
+    }
+
+    /// Shared checks of every outbound ticket. Minted tokens come back with the burn message,
+    /// escrowed tokens come back without one: their coins are locked from `info.funds`. The fee
+    /// of a `from_hook` ticket is paid in the transferred coin, see `hook_fee`. `token_id` is
+    /// the local id.
+    fn prepare_ticket(
+        deps: Deps,
+        env: &Env,
+        info: &MessageInfo,
+        params: TicketParams,
+    ) -> Result<(Option<CosmosMsg>, GenerateTicketReq), ContractError> {
+        let TicketParams {
+            token_id,
+            sender,
+            receiver,
+            amount,
+            target_chain,
+            action,
+            memo,
+            from_hook,
+        } = params;
+        let token = read_state(deps.storage, |s| match s.tokens.get(&token_id) {
+            Some(token) => Ok(token.clone()),
+            None => Err(ContractError::TokenNotFound),
+        })?;
+        // removed tokens can still be sent out until their sunset ends
+        if read_state(deps.storage, |s| s.is_sunset_over(&token_id, env)) {
+            return Err(ContractError::TokenRemoved);
+        }
+
+        check_target_chain(deps, target_chain.clone())?;
+        check_token_route(deps, &token_id, &target_chain)?;
+        check_receiver(deps, &target_chain, &receiver)?;
+        if let Some(memo) = &memo {
+            check_memo(deps, &target_chain, memo)?;
+        }
+        let escrow_denom = read_state(deps.storage, |s| s.escrow_tokens.get(&token_id).cloned());
+        let locked = match escrow_denom {
+            Some(denom) => {
+                if action != crate::state::TxAction::Transfer {
+                    return Err(ContractError::EscrowTokenRequiresTransfer);
+                }
+                Some(cosmwasm_std::Coin::new(parse_amount(&amount)?, denom))
+            }
+            None => None,
+        };
+        let (fee_token, fee_amount) = match from_hook {
+            true => hook_fee(deps, &token_id, &target_chain)?,
+            false => check_fee(deps, info, target_chain.clone(), locked.as_ref())?,
+        };
+        if let Some(locked) = &locked {
+            check_locked_funds(info, locked, &fee_token, fee_amount)?;
+        }
+        check_min_amount(deps, &token_id, &target_chain, &amount)?;
+
+        let burn_msg = match locked {
+            Some(_) => None,
+            None => Some(build_burn_msg(
+                env.contract.address.clone(),
+                info.sender.clone(),
+                read_state(deps.storage, |s| s.token_denom(&token.token_id))?,
+                amount.clone(),
+            )),
+        };
+
+        let state = STATE.load(deps.storage)?;
+        let generate_ticket_req = GenerateTicketReq {
+            seq: state.generate_ticket_sequence,
+            target_chain_id: target_chain,
+            sender,
+            receiver,
+            token_id: state.token_ids.omnity_id(&token_id),
+            amount,
+            action,
+            timestamp: env.block.time.nanos(),
+            block_height: env.block.height,
+            memo,
+            fee_token,
+            fee_amount: fee_amount.to_string(),
+            on_behalf_of: None,
+            refund_to: None,
+        };
+
+        Ok((burn_msg, generate_ticket_req))
+    }
+
+    /// Fee of an ibc hook ticket, in the token's own denom as the transfer carries no other
+    /// coin. A token bridged as the fee token pays the usual fee, any other token needs a hook
+    /// fee factor. The funds are checked against the ticket amount plus this fee.
+    pub fn hook_fee(
+        deps: Deps,
+        token_id: &str,
+        target_chain: &str,
+    ) -> Result<(String, u128), ContractError> {
+        let (denom, fee_token, factor) = read_state(deps.storage, |s| {
+            Ok::<_, ContractError>((
+                s.token_denom(token_id)?,
+                s.fee_token.clone().ok_or(ContractError::FeeHasNotSet)?,
+                s.hook_fee_factors.get(token_id).copied(),
+            ))
+        })?;
+        if denom == fee_token {
+            return Ok((denom, calculate_fee(deps, target_chain.to_string())?));
+        }
+        let factor = factor.ok_or(ContractError::FeeHasNotSet)?;
+        let chain_factor = read_state(deps.storage, |s| {
+            s.target_chain_factor
+                .get(target_chain)
+                .cloned()
+                .ok_or(ContractError::FeeHasNotSet)
+        })?;
+        Ok((denom, factor.u128() * chain_factor))
+    }
+
+    /// Books the coins attached to an escrowed ticket as locked by the contract.
+    fn lock_escrow(
+        storage: &mut dyn Storage,
+        req: &GenerateTicketReq,
+    ) -> Result<Event, ContractError> {
+        let token_id = read_state(storage, |s| s.token_ids.local_id(&req.token_id));
+        let amount = parse_amount(&req.amount)?;
+        let mut state = STATE.load(storage)?;
+        let denom = state
+            .escrow_tokens
+            .get(&token_id)
+            .cloned()
+            .ok_or(ContractError::TokenNotEscrowed)?;
+        *state.escrow_balances.entry(token_id.clone()).or_default() += amount;
+        STATE.save(storage, &state)?;
+
+        Ok(Event::new("TokenLocked").add_attributes(vec![
+            Attribute::new("token_id", token_id),
+            Attribute::new("denom", denom),
+            Attribute::new("sender", req.sender.clone()),
+            Attribute::new("amount", req.amount.clone()),
+        ]))
+    }
+
+    /// Pays out an inbound ticket of an escrowed token from the coins locked by the contract.
+    fn release_escrow(
+        deps: DepsMut,
+        ticket_id: String,
+        token_id: String,
+        receiver: Addr,
+        denom: String,
+        amount: String,
+    ) -> Result<Response, ContractError> {
+        let amount_u128 = parse_amount(&amount)?;
+        unlock_escrow(deps.storage, &token_id, amount_u128)?;
+
+        let bank_msg = CosmosMsg::Bank(BankMsg::Send {
+            to_address: receiver.to_string(),
+            amount: vec![cosmwasm_std::Coin::new(amount_u128, denom.clone())],
+        });
+
+        let mint_token_payload = MintTokenPayload {
+            ticket_id,
+            token_id: token_id.clone(),
+            receiver: receiver.clone(),
+            amount: amount.clone(),
+            transmuter: None,
+        };
+
+        Ok(Response::new()
+            .add_event(Event::new("TokenUnlocked").add_attributes(vec![
+                Attribute::new("token_id", token_id),
+                Attribute::new("denom", denom),
+                Attribute::new("receiver", receiver),
+                Attribute::new("amount", amount),
+            ]))
+            .add_submessage(
+                SubMsg::reply_on_success(bank_msg, reply_msg_id::MINT_TOKEN_REPLY_ID).with_payload(
+                    serde_json::to_vec(&mint_token_payload)
+                        .map_err(|e| ContractError::CustomError(e.to_string()))?,
+                ),
+            ))
+    }
+
+    /// Takes coins leaving the contract out of the locked balance of an escrowed token.
+    pub fn unlock_escrow(
+        storage: &mut dyn Storage,
+        token_id: &str,
+        amount: u128,
+    ) -> Result<(), ContractError> {
+        STATE.update(storage, |mut state| -> Result<_, ContractError> {
+            let balance = state
+                .escrow_balances
+                .entry(token_id.to_string())
+                .or_default();
+            if *balance < amount {
+                return Err(ContractError::InsufficientEscrow(*balance, amount));
+            }
+            *balance -= amount;
+            Ok(state)
+        })?;
+        Ok(())
+    }
+
+    pub fn claim_balance(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+        let coins = take_claimable(deps.storage, &info.sender)?;
+        if coins.is_empty() {
+            return Err(ContractError::NoClaimableBalance);
+        }
+
+        Ok(Response::new()
+            .add_event(Event::new("BalanceClaimed").add_attributes(vec![
+                Attribute::new("beneficiary", info.sender.clone()),
+                Attribute::new("amount", format!("{:?}", coins)),
+            ]))
+            .add_message(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: coins,
+            }))
+    }
+
+    /// Consumes the current generate ticket sequence, returning it.
+    pub fn next_ticket_seq(storage: &mut dyn Storage) -> Result<u64, ContractError> {
+        let mut state = STATE.load(storage)?;
+        let current_seq = state.generate_ticket_sequence;
+        state.generate_ticket_sequence += 1;
+        STATE.save(storage, &state)?;
+        Ok(current_seq)
+    }
+
+    /// Schedules the route change, the route can mint at will so it is timelocked.
+    pub fn update_route(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        route: Addr,
+    ) -> Result<Response, ContractError> {
+        if read_state(deps.storage, |s| info.sender != s.admin) {
+            return Err(ContractError::Unauthorized);
+        }
+
+        schedule_operation(
+            deps.storage,
+            &env,
+            &info.sender,
+            TimelockedAction::UpdateRoute { route },
+        )
+    }
+
+    /// Schedules the counterpart port and the connection it is reached over. Packets from it
+    /// mint like the route, so it is timelocked like a route update.
+    pub fn set_ibc_port(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        port: Option<CounterpartPort>,
+    ) -> Result<Response, ContractError> {
+        if read_state(deps.storage, |s| info.sender != s.admin) {
+            return Err(ContractError::Unauthorized);
+        }
+
+        schedule_operation(
+            deps.storage,
+            &env,
+            &info.sender,
+            TimelockedAction::SetIbcPort { port },
+        )
+    }
+
+    /// Schedules a new relay reward, paid out of collected fees so it is timelocked like a
+    /// fee withdrawal.
+    pub fn set_relay_reward(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        reward: Option<Uint128>,
+    ) -> Result<Response, ContractError> {
+        if read_state(deps.storage, |s| info.sender != s.admin) {
+            return Err(ContractError::Unauthorized);
+        }
+
+        schedule_operation(
+            deps.storage,
+            &env,
+            &info.sender,
+            TimelockedAction::SetRelayReward { reward },
+        )
+    }
+
+    pub fn claim_relay_rewards(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+    ) -> Result<Response, ContractError> {
+        let mut stats = RELAYERS
+            .may_load(deps.storage, &info.sender)?
+            .filter(|stats| !stats.owed().is_zero())
+            .ok_or(ContractError::NoRelayRewards)?;
+        let payout = pay_relayer(deps, &env, &mut stats)?;
+        if payout.messages.is_empty() {
+            return Err(ContractError::NoRelayRewards);
+        }
+        Ok(payout)
+    }
+
+    /// Books a relayed ticket and its reward, then pays whatever the relayer is owed.
+    fn reward_relayer(deps: DepsMut, env: &Env, relayer: &Addr) -> Result<Response, ContractError> {
+        let reward = read_state(deps.storage, |s| {
+            s.fee_token.as_ref().and(s.relay_reward).unwrap_or_default()
+        });
+        let mut stats = RELAYERS
+            .may_load(deps.storage, relayer)?
+            .unwrap_or(RelayerStats {
+                relayer: relayer.clone(),
+                tickets: 0,
+                earned: Uint128::zero(),
+                paid: Uint128::zero(),
+            });
+        RELAYER_RANKING.remove(deps.storage, (stats.tickets, relayer));
+        stats.tickets += 1;
+        stats.earned += reward;
+        RELAYER_RANKING.save(deps.storage, (stats.tickets, relayer), &())?;
+        pay_relayer(deps, env, &mut stats)
+    }
+
+    /// Pays the rewards owed to a relayer as far as the collected fees allow and saves the
+    /// ledger entry. Coins of escrowed tokens sharing the fee denom are never used.
+    fn pay_relayer(
+        deps: DepsMut,
+        env: &Env,
+        stats: &mut RelayerStats,
+    ) -> Result<Response, ContractError> {
+        let mut response = Response::new();
+        let fee_token = read_state(deps.storage, |s| s.fee_token.clone());
+        if let Some(fee_token) = fee_token.filter(|_| !stats.owed().is_zero()) {
+            let locked = reserved_balance(deps.storage, &fee_token)?;
+            let balance = deps
+                .querier
+                .query_balance(&env.contract.address, &fee_token)?
+                .amount
+                .u128();
+            let payout = stats.owed().u128().min(balance.saturating_sub(locked));
+            if payout > 0 {
+                stats.paid += Uint128::new(payout);
+                response = response
+                    .add_message(CosmosMsg::Bank(BankMsg::Send {
+                        to_address: stats.relayer.to_string(),
+                        amount: vec![cosmwasm_std::Coin::new(payout, fee_token.clone())],
+                    }))
+                    .add_event(Event::new("RelayRewardPaid").add_attributes(vec![
+                        Attribute::new("relayer", stats.relayer.clone()),
+                        Attribute::new("amount", payout.to_string()),
+                        Attribute::new("denom", fee_token),
+                        Attribute::new("owed", stats.owed().to_string()),
+                    ]));
+            }
+        }
+        RELAYERS.save(deps.storage, &stats.relayer, stats)?;
+        Ok(response)
+    }
+
+    /// Coins of `denom` held for users, escrowed coins and failed forwards, which are never
+    /// the contract's to pay out.
+    pub fn reserved_balance(storage: &dyn Storage, denom: &str) -> Result<u128, ContractError> {
+        let escrowed = read_state(storage, |s| s.escrowed_amount(denom));
+        let claimable = CLAIMABLE_TOTALS.may_load(storage, denom)?.unwrap_or_default();
+        Ok(escrowed + claimable)
+    }
+
+    /// Schedules a new attestor set, timelocked like the route it stands in for.
+    pub fn set_attestors(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        attestors: Option<AttestorSet>,
+    ) -> Result<Response, ContractError> {
+        if read_state(deps.storage, |s| info.sender != s.admin) {
+            return Err(ContractError::Unauthorized);
+        }
+        if let Some(attestors) = &attestors {
+            attestors.validate()?;
+        }
+
+        schedule_operation(
+            deps.storage,
+            &env,
+            &info.sender,
+            TimelockedAction::SetAttestors { attestors },
+        )
+    }
+
+    /// Schedules a new timelock delay, itself timelocked so a shorter delay cannot be used to
+    /// rush other operations.
+    pub fn set_timelock_delay(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        delay_secs: Option<u64>,
+    ) -> Result<Response, ContractError> {
+        if read_state(deps.storage, |s| info.sender != s.admin) {
+            return Err(ContractError::Unauthorized);
+        }
+        if let Some(delay_secs) = delay_secs {
+            if delay_secs < MIN_TIMELOCK_SECS {
+                return Err(ContractError::TimelockTooShort(MIN_TIMELOCK_SECS));
+            }
+        }
+
+        schedule_operation(
+            deps.storage,
+            &env,
+            &info.sender,
+            TimelockedAction::SetTimelockDelay { delay_secs },
+        )
+    }
+
+    pub fn redeem_setting(
+        deps: DepsMut,
+        info: MessageInfo,
+        token_id: String,
+        target_chain: String,
+        min_amount: String,
+    ) -> Result<Response, ContractError> {
+        if read_state(deps.storage, |s| info.sender != s.admin) {
+            return Err(ContractError::Unauthorized);
+        }
+        let token_id = read_state(deps.storage, |s| s.token_ids.local_id(&token_id));
+
+        STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+            state
+                .target_chain_redeem_min_amount
+                .insert((token_id.clone(), target_chain.clone()), min_amount.clone());
+            Ok(state)
+        })?;
+
+        Ok(
+            Response::new().add_event(Event::new("RedeemSettingUpdated").add_attributes(vec![
+                Attribute::new("token_id", token_id),
+                Attribute::new("target_chain", target_chain),
+                Attribute::new("min_amount", min_amount),
+            ])),
+        )
+    }
+
+    pub fn approve(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        operator: String,
+        expires_at: Option<u64>,
+    ) -> Result<Response, ContractError> {
+        let operator = deps.api.addr_validate(&operator)?;
+        if operator == info.sender {
+            return Err(ContractError::CustomError(
+                "Cannot approve yourself".to_string(),
+            ));
+        }
+        if expires_at.is_some_and(|expires_at| expires_at <= env.block.time.seconds()) {
+            return Err(ContractError::CustomError(
+                "Approval expires in the past".to_string(),
+            ));
+        }
+
+        let approval = Approval {
+            granter: info.sender.clone(),
+            operator: operator.clone(),
+            granted_at: env.block.time.seconds(),
+            expires_at,
+        };
+        APPROVALS.save(deps.storage, (&info.sender, &operator), &approval)?;
+
+        Ok(
+            Response::new().add_event(Event::new("ApprovalGranted").add_attributes(vec![
+                Attribute::new("granter", info.sender),
+                Attribute::new("operator", operator),
+                Attribute::new(
+                    "expires_at",
+                    expires_at.map(|e| e.to_string()).unwrap_or_default(),
+                ),
+            ])),
+        )
+    }
+
+    pub fn revoke_approval(
+        deps: DepsMut,
+        info: MessageInfo,
+        operator: String,
+    ) -> Result<Response, ContractError> {
+        let operator = deps.api.addr_validate(&operator)?;
+        if !APPROVALS.has(deps.storage, (&info.sender, &operator)) {
+            return Err(ContractError::ApprovalNotFound);
+        }
+        APPROVALS.remove(deps.storage, (&info.sender, &operator));
+
+        Ok(
+            Response::new().add_event(Event::new("ApprovalRevoked").add_attributes(vec![
+                Attribute::new("granter", info.sender),
+                Attribute::new("operator", operator),
+            ])),
+        )
+    }
+
+    pub fn set_chain_key_token(
+        deps: DepsMut,
+        info: MessageInfo,
+        token_id: String,
+        chain_key_token: Option<IcpChainKeyToken>,
+    ) -> Result<Response, ContractError> {
+        if read_state(deps.storage, |s| info.sender != s.admin) {
+            return Err(ContractError::Unauthorized);
+        }
+
+        let token_id = read_state(deps.storage, |s| s.token_ids.local_id(&token_id));
+        if read_state(deps.storage, |s| !s.tokens.contains_key(&token_id)) {
+            return Err(ContractError::TokenNotFound);
+        }
+
+        STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+            match chain_key_token.clone() {
+                Some(chain_key_token) => {
+                    state
+                        .chain_key_tokens
+                        .insert(token_id.clone(), chain_key_token);
+                }
+                None => {
+                    state.chain_key_tokens.remove(&token_id);
+                }
+            }
+            Ok(state)
+        })?;
+
+        Ok(
+            Response::new().add_event(Event::new("ChainKeyTokenUpdated").add_attributes(vec![
+                Attribute::new("token_id", token_id),
+                Attribute::new(
+                    "chain_key_token",
+                    serde_json::to_string(&chain_key_token)
+                        .map_err(|e| ContractError::CustomError(e.to_string()))?,
+                ),
+            ])),
+        )
+    }
+
+    /// Sets the receiver validator of a counterparty chain outside of its `AddChain` and
+    /// `UpdateChain` directives.
+    pub fn set_receiver_validator(
+        deps: DepsMut,
+        info: MessageInfo,
+        chain_id: String,
+        validator: Option<ReceiverValidator>,
+    ) -> Result<Response, ContractError> {
+        if read_state(deps.storage, |s| info.sender != s.admin) {
+            return Err(ContractError::Unauthorized);
+        }
+
+        STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+            let chain = state
+                .counterparties
+                .get_mut(&chain_id)
+                .ok_or(ContractError::ChainNotFound)?;
+            chain.receiver_validator = validator.clone();
+            Ok(state)
+        })?;
+
+        let validator = serde_json::to_string(&validator)
+            .map_err(|e| ContractError::CustomError(e.to_string()))?;
+        Ok(
+            Response::new().add_event(Event::new("ReceiverValidatorUpdated").add_attributes(vec![
+                Attribute::new("chain_id", chain_id),
+                Attribute::new("validator", validator),
+            ])),
+        )
+    }
+
+    pub fn set_token_routes(
+        deps: DepsMut,
+        info: MessageInfo,
+        token_id: String,
+        routes: Option<TokenRoutes>,
+    ) -> Result<Response, ContractError> {
+        if read_state(deps.storage, |s| info.sender != s.admin) {
+            return Err(ContractError::Unauthorized);
+        }
+        let token_id = read_state(deps.storage, |s| s.token_ids.local_id(&token_id));
+
+        STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+            if !state.tokens.contains_key(&token_id) {
+                return Err(ContractError::TokenNotFound);
+            }
+            match routes.clone() {
+                Some(routes) => {
+                    state.token_routes.insert(token_id.clone(), routes);
+                }
+                None => {
+                    state.token_routes.remove(&token_id);
+                }
+            }
+            Ok(state)
+        })?;
+
+        let routes = serde_json::to_string(&routes)
+            .map_err(|e| ContractError::CustomError(e.to_string()))?;
+        Ok(
+            Response::new().add_event(Event::new("TokenRoutesUpdated").add_attributes(vec![
+                Attribute::new("token_id", token_id),
+                Attribute::new("routes", routes),
+            ])),
+        )
+    }
+
+    pub fn set_hook_fee_factor(
+        deps: DepsMut,
+        info: MessageInfo,
+        token_id: String,
+        factor: Option<Uint128>,
+    ) -> Result<Response, ContractError> {
+        if read_state(deps.storage, |s| info.sender != s.admin) {
+            return Err(ContractError::Unauthorized);
+        }
+        let token_id = read_state(deps.storage, |s| s.token_ids.local_id(&token_id));
+
+        STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+            if !state.tokens.contains_key(&token_id) {
+                return Err(ContractError::TokenNotFound);
+            }
+            match factor {
+                Some(factor) => state.hook_fee_factors.insert(token_id.clone(), factor),
+                None => state.hook_fee_factors.remove(&token_id),
+            };
+            Ok(state)
+        })?;
+
+        Ok(
+            Response::new().add_event(Event::new("HookFeeFactorUpdated").add_attributes(vec![
+                Attribute::new("token_id", token_id),
+                Attribute::new("factor", factor.unwrap_or_default().to_string()),
+            ])),
+        )
+    }
+
+    pub fn set_max_memo_length(
+        deps: DepsMut,
+        info: MessageInfo,
+        max_memo_length: Option<u32>,
+    ) -> Result<Response, ContractError> {
+        if read_state(deps.storage, |s| info.sender != s.admin) {
+            return Err(ContractError::Unauthorized);
+        }
+
+        let state = STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+            state.max_memo_length = max_memo_length;
+            Ok(state)
+        })?;
+
+        Ok(
+            Response::new().add_event(Event::new("MaxMemoLengthUpdated").add_attributes(vec![
+                Attribute::new("max_memo_length", state.max_memo_length().to_string()),
+            ])),
+        )
+    }
+
+    /// Local id of a token the admin may still bind to an existing denom. The mode of a token
+    /// is fixed once the token is added.
+    fn bindable_token_id(
+        storage: &dyn Storage,
+        info: &MessageInfo,
+        token_id: &str,
+    ) -> Result<TokenId, ContractError> {
+        read_state(storage, |s| {
+            if info.sender != s.admin {
+                return Err(ContractError::Unauthorized);
+            }
+            let token_id = s.token_ids.local_id(token_id);
+            if s.tokens.contains_key(&token_id) {
+                return Err(ContractError::TokenAleardyExist);
+            }
+            Ok(token_id)
+        })
+    }
+
+    pub fn set_escrow_token(
+        deps: DepsMut,
+        info: MessageInfo,
+        token_id: String,
+        denom: Option<String>,
+    ) -> Result<Response, ContractError> {
+        let token_id = bindable_token_id(deps.storage, &info, &token_id)?;
+        if let Some(denom) = denom.as_ref().filter(|denom| !is_valid_denom(denom)) {
+            return Err(ContractError::InvalidDenom(denom.clone()));
+        }
+
+        STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+            match denom.clone() {
+                Some(denom) => {
+                    state.adopted_denoms.remove(&token_id);
+                    state.escrow_tokens.insert(token_id.clone(), denom);
+                }
+                None => {
+                    state.escrow_tokens.remove(&token_id);
+                }
+            }
+            Ok(state)
+        })?;
+
+        Ok(
+            Response::new().add_event(Event::new("EscrowTokenUpdated").add_attributes(vec![
+                Attribute::new("token_id", token_id),
+                Attribute::new("denom", denom.unwrap_or_default()),
+            ])),
+        )
+    }
+
+    /// Binds a token to an existing tokenfactory denom before the token is added, the admin
+    /// of the denom must have been changed to the contract for mints and burns to succeed.
+    pub fn set_adopted_denom(
         deps: DepsMut,
+        env: Env,
         info: MessageInfo,
         token_id: String,
-        target_chain: String,
-        min_amount: String,
+        denom: Option<String>,
     ) -> Result<Response, ContractError> {
-        if read_state(deps.storage, |s| info.sender != s.admin) {
-            return Err(ContractError::Unauthorized);
+        let token_id = bindable_token_id(deps.storage, &info, &token_id)?;
+        if let Some(denom) = &denom {
+            // only factory denoms can be minted, other denoms are bridged through escrow
+            if !denom.starts_with("factory/") || !is_valid_denom(denom) {
+                return Err(ContractError::InvalidDenom(denom.clone()));
+            }
+            // tokens are minted through the adopted denom, so the contract must administer it
+            let admin = TokenfactoryQuerier::new(&deps.querier)
+                .denom_authority_metadata(denom.clone())?
+                .authority_metadata
+                .map(|m| m.admin)
+                .unwrap_or_default();
+            if admin != env.contract.address.as_str() {
+                return Err(ContractError::NotDenomAdmin(denom.clone()));
+            }
         }
 
         STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
-            state
-                .target_chain_redeem_min_amount
-                .insert((token_id.clone(), target_chain.clone()), min_amount.clone());
+            match denom.clone() {
+                Some(denom) => {
+                    state.escrow_tokens.remove(&token_id);
+                    state.adopted_denoms.insert(token_id.clone(), denom);
+                }
+                None => {
+                    state.adopted_denoms.remove(&token_id);
+                }
+            }
             Ok(state)
         })?;
 
         Ok(
-            Response::new().add_event(Event::new("RedeemSettingUpdated").add_attributes(vec![
+            Response::new().add_event(Event::new("AdoptedDenomUpdated").add_attributes(vec![
                 Attribute::new("token_id", token_id),
-                Attribute::new("target_chain", target_chain),
-                Attribute::new("min_amount", min_amount),
+                Attribute::new("denom", denom.unwrap_or_default()),
             ])),
         )
     }
 
+    /// Schedules sending coins held by the contract to `receiver`.
     pub fn refund_token(
         deps: DepsMut,
-        _env: Env,
+        env: Env,
         info: &MessageInfo,
         denom: String,
         receiver: String,
@@ -717,81 +2392,193 @@ pub mod execute {
         }) {
             return Err(ContractError::Unauthorized);
         }
+        // fail now rather than once the timelock has passed
+        build_contract_transfer_msg(denom.clone(), amount.clone(), receiver.clone())?;
+
+        schedule_operation(
+            deps.storage,
+            &env,
+            &info.sender,
+            TimelockedAction::RefundToken {
+                denom,
+                receiver,
+                amount,
+            },
+        )
+    }
 
-        let msg = build_contract_transfer_msg(denom, amount, receiver)?;
-        Ok(Response::new().add_message(msg))
+    /// Refunds only come out of coins the contract owns, never out of `reserved_balance`.
+    fn check_refundable(
+        deps: Deps,
+        env: &Env,
+        denom: &str,
+        amount: &str,
+    ) -> Result<(), ContractError> {
+        let amount = parse_amount(amount)?;
+        let balance = deps
+            .querier
+            .query_balance(&env.contract.address, denom)?
+            .amount
+            .u128();
+        let available = balance.saturating_sub(reserved_balance(deps.storage, denom)?);
+        if amount > available {
+            return Err(ContractError::InsufficientRefundable(available, amount));
+        }
+        Ok(())
     }
 
     pub fn execute_update_token_msg(
         deps: DepsMut,
         env: Env,
-        _info: &MessageInfo,
+        info: &MessageInfo,
         token_id: String,
         name: String,
         symbol: String,
         decimals: u8,
         icon: Option<String>,
     ) -> Result<Response, ContractError> {
-        if read_state(deps.storage, |s| !s.tokens.contains_key(&token_id)) {
-            Err(ContractError::TokenNotFound)
-        } else {
-            let sender = env.contract.address.to_string();
-
-            STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
-                let mut token = state
-                    .tokens
-                    .get(&token_id)
-                    .ok_or(ContractError::TokenNotFound)?
-                    .clone();
-                token.name = name.clone();
-                token.symbol = symbol.clone();
-                token.decimals = decimals;
-                token.icon = icon.clone();
-                state.tokens.insert(token_id.clone(), token);
-                Ok(state)
-            })?;
-
-            let token = read_state(deps.storage, |s| {
-                s.tokens
-                    .get(&token_id)
-                    .map(|t| t.clone())
-                    .ok_or(ContractError::TokenNotFound)
-            })?;
-
-            let token_base_denom = token_denom(env.contract.address.to_string(), token.token_id);
-            let set_denom_metadata_msg = MsgSetDenomMetadata {
-                sender: sender.clone(),
-                metadata: Some(Metadata {
-                    description: token.name.clone(),
-                    denom_units: vec![
-                        DenomUnit {
-                            denom: token_base_denom.clone(),
-                            exponent: 0,
-                            aliases: vec![],
-                        },
-                        DenomUnit {
-                            denom: token.symbol.clone(),
-                            exponent: token.decimals as u32,
-                            aliases: vec![],
-                        },
-                    ],
-                    base: token_base_denom,
-                    display: token.symbol.clone(),
-                    name: token.name,
-                    symbol: token.symbol,
-                    uri: token.icon.unwrap_or("".to_string()),
-                    uri_hash: "".to_string(),
-                }),
-            };
-            let update_msg = CosmosMsg::Stargate {
-                type_url: "/osmosis.tokenfactory.v1beta1.MsgSetDenomMetadata".to_string(),
-                value: set_denom_metadata_msg.encode_to_vec().into(),
-            };
+        if read_state(deps.storage, |s| info.sender != s.admin) {
+            return Err(ContractError::Unauthorized);
+        }
+        let token_id = read_state(deps.storage, |s| s.token_ids.local_id(&token_id));
+        let previous = read_state(deps.storage, |s| {
+            s.tokens
+                .get(&token_id)
+                .cloned()
+                .ok_or(ContractError::TokenNotFound)
+        })?;
+        let token = Token {
+            name,
+            symbol,
+            decimals,
+            icon,
+            ..previous.clone()
+        };
+        apply_token_update(deps, &env, &info.sender, previous, token)
+    }
+
+    /// Replaces `previous` by `token`, recording the change. The decimals are locked once the
+    /// token has a supply on this chain.
+    fn apply_token_update(
+        deps: DepsMut,
+        env: &Env,
+        sender: &Addr,
+        previous: Token,
+        token: Token,
+    ) -> Result<Response, ContractError> {
+        if token.decimals != previous.decimals
+            && token_supply(deps.as_ref(), &token.token_id)? > 0
+        {
+            return Err(ContractError::DecimalsLocked);
+        }
+        STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+            state.tokens.insert(token.token_id.clone(), token.clone());
+            Ok(state)
+        })?;
+        let seq = record_token_change(deps.storage, env, sender, Some(previous), &token)?;
+
+        let mut response =
+            Response::new().add_event(Event::new("TokenUpdated").add_attributes(vec![
+                Attribute::new("token_id", token.token_id.clone()),
+                Attribute::new("seq", seq.to_string()),
+            ]));
+        // escrowed and adopted tokens keep the metadata of their existing denom
+        if read_state(deps.storage, |s| s.bound_denom(&token.token_id).is_none()) {
+            let denom = read_state(deps.storage, |s| s.token_denom(&token.token_id))?;
+            response = response.add_message(build_set_denom_metadata_msg(
+                &env.contract.address,
+                denom,
+                &token,
+            )?);
+        }
+        Ok(response)
+    }
+
+    /// Amount of `token_id` in circulation on this chain: the factory denom supply, or the
+    /// locked balance of an escrowed token.
+    pub fn token_supply(deps: Deps, token_id: &str) -> Result<u128, ContractError> {
+        let escrowed = read_state(deps.storage, |s| {
+            s.escrow_tokens
+                .contains_key(token_id)
+                .then(|| s.escrow_balances.get(token_id).cloned().unwrap_or_default())
+        });
+        match escrowed {
+            Some(balance) => Ok(balance),
+            None => {
+                let denom = read_state(deps.storage, |s| s.token_denom(token_id))?;
+                Ok(deps.querier.query_supply(denom)?.amount.u128())
+            }
+        }
+    }
+
+    /// Appends a token metadata change to its history, returns the sequence of the change.
+    pub fn record_token_change(
+        storage: &mut dyn Storage,
+        env: &Env,
+        updated_by: &Addr,
+        previous: Option<Token>,
+        current: &Token,
+    ) -> Result<u64, ContractError> {
+        let seq = TOKEN_METADATA_HISTORY
+            .prefix(&current.token_id)
+            .keys(storage, None, None, Order::Descending)
+            .next()
+            .transpose()?
+            .map_or(0, |seq| seq + 1);
+        TOKEN_METADATA_HISTORY.save(
+            storage,
+            (&current.token_id, seq),
+            &TokenMetadataChange {
+                seq,
+                updated_by: updated_by.clone(),
+                timestamp: env.block.time.seconds(),
+                block_height: env.block.height,
+                previous,
+                current: current.clone(),
+            },
+        )?;
+        Ok(seq)
+    }
+
+    pub fn build_set_denom_metadata_msg(
+        contract_addr: &Addr,
+        denom: String,
+        token: &Token,
+    ) -> Result<CosmosMsg, ContractError> {
+        let msg = MsgSetDenomMetadata {
+            sender: contract_addr.to_string(),
+            metadata: Some(denom_metadata(denom, token)?),
+        };
+        Ok(stargate_msg(
+            "/osmosis.tokenfactory.v1beta1.MsgSetDenomMetadata",
+            msg.encode_to_vec(),
+        ))
+    }
 
-            Ok(Response::new().add_message(update_msg))
+    /// Message to one of the chain's modules. Every such message is built here, the only place
+    /// the deprecated `CosmosMsg::Stargate` is allowed.
+    #[allow(deprecated)]
+    pub fn stargate_msg(type_url: &str, value: Vec<u8>) -> CosmosMsg {
+        CosmosMsg::Stargate {
+            type_url: type_url.to_string(),
+            value: Binary::new(value),
         }
     }
 
+    pub fn build_mint_msg(
+        contract_addr: Addr,
+        receiver: String,
+        denom: String,
+        amount: String,
+    ) -> CosmosMsg {
+        let msg = MsgMint {
+            sender: contract_addr.to_string(),
+            amount: Some(Coin { denom, amount }),
+            mint_to_address: receiver,
+        };
+        stargate_msg("/osmosis.tokenfactory.v1beta1.MsgMint", msg.encode_to_vec())
+    }
+
     pub fn build_burn_msg(
         contract_addr: Addr,
         sender: Addr,
@@ -803,10 +2590,7 @@ pub mod execute {
             amount: Some(Coin { denom, amount }),
             burn_from_address: sender.to_string(),
         };
-        CosmosMsg::Stargate {
-            type_url: "/osmosis.tokenfactory.v1beta1.MsgBurn".into(),
-            value: Binary::new(msg.encode_to_vec()),
-        }
+        stargate_msg("/osmosis.tokenfactory.v1beta1.MsgBurn", msg.encode_to_vec())
     }
 
     pub fn build_contract_transfer_msg(
@@ -823,8 +2607,8 @@ pub mod execute {
         }))
     }
 
-    fn check_min_amount(
-        deps: &DepsMut,
+    pub fn check_min_amount(
+        deps: Deps,
         token_id: &String,
         target_chain: &String,
         amount: &String,
@@ -837,7 +2621,7 @@ pub mod execute {
                 .unwrap_or("0".to_string())
         });
 
-        if amount.parse::<u128>().unwrap() < min_amount.parse::<u128>().unwrap() {
+        if parse_amount(amount)? < parse_amount(&min_amount)? {
             return Err(ContractError::RedeemAmountLessThanMinAmount(
                 min_amount,
                 amount.clone(),
@@ -847,24 +2631,63 @@ pub mod execute {
         Ok(())
     }
 
-    fn check_target_chain(deps: &DepsMut, target_chain: String) -> Result<(), ContractError> {
-        let counterparties = read_state(deps.storage, |state| state.counterparties.clone());
+    pub fn check_chain_active(deps: Deps) -> Result<(), ContractError> {
+        if read_state(deps.storage, |s| s.chain_state != ChainState::Active) {
+            return Err(ContractError::ChainDeactive);
+        }
+        Ok(())
+    }
+
+    pub fn check_target_chain(deps: Deps, target_chain: String) -> Result<(), ContractError> {
+        read_state(deps.storage, |s| s.check_target_chain(&target_chain))
+    }
 
-        if let Some(target_chain) = counterparties.get(&target_chain) {
-            if target_chain.chain_state == ChainState::Active {
-                return Ok(());
-            } else {
-                return Err(ContractError::TargetChainDeactive);
-            }
-        } else {
-            return Err(ContractError::TargetChainNotFound);
+    pub fn check_token_route(
+        deps: Deps,
+        token_id: &str,
+        target_chain: &str,
+    ) -> Result<(), ContractError> {
+        let permitted = read_state(deps.storage, |s| {
+            s.token_routes
+                .get(token_id)
+                .map_or(true, |routes| routes.permits(target_chain))
+        });
+        if !permitted {
+            return Err(ContractError::TokenRouteNotAllowed);
+        }
+        Ok(())
+    }
+
+    pub fn check_receiver(
+        deps: Deps,
+        target_chain: &str,
+        receiver: &str,
+    ) -> Result<(), ContractError> {
+        match read_state(deps.storage, |s| {
+            s.counterparties
+                .get(target_chain)
+                .and_then(|chain| chain.receiver_validator.clone())
+        }) {
+            Some(validator) => validator.validate(receiver),
+            None => Ok(()),
         }
     }
 
-    fn check_fee(
-        deps: &DepsMut,
+    /// Size limit and reserved fields of the memo, checked before anything is burnt or locked.
+    pub fn check_memo(deps: Deps, target_chain: &str, memo: &str) -> Result<(), ContractError> {
+        let max_memo_length = read_state(deps.storage, |s| s.max_memo_length());
+        if let Some(call) = validate_memo(memo, max_memo_length)?.and_then(|memo| memo.call) {
+            // the downstream contract lives on the target chain
+            check_receiver(deps, target_chain, &call.contract)?;
+        }
+        Ok(())
+    }
+
+    pub fn check_fee(
+        deps: Deps,
         info: &MessageInfo,
         target_chain: String,
+        locked: Option<&cosmwasm_std::Coin>,
     ) -> Result<(String, u128), ContractError> {
         let fee_token = read_state(deps.storage, |state| {
             state.fee_token.clone().ok_or(ContractError::FeeHasNotSet)
@@ -872,12 +2695,16 @@ pub mod execute {
 
         let fee = calculate_fee(deps, target_chain)?;
         let funds_info = format!("{:?}", info.funds);
-        let attached_fee = info
+        let mut attached_fee = info
             .funds
             .iter()
             .find(|coin| coin.denom == fee_token)
             .map(|c| c.amount.u128())
             .unwrap_or(0);
+        // coins locked in escrow may share the fee denom
+        if let Some(locked) = locked.filter(|c| c.denom == fee_token) {
+            attached_fee = attached_fee.saturating_sub(locked.amount.u128());
+        }
 
         if attached_fee != fee {
             return Err(ContractError::IncorrectFee(fee, attached_fee, funds_info));
@@ -886,7 +2713,34 @@ pub mod execute {
         Ok((fee_token, fee))
     }
 
-    pub fn calculate_fee(deps: &DepsMut, target_chain: String) -> Result<u128, ContractError> {
+    /// Ensures the funds carry exactly the coins an escrowed ticket locks, on top of the fee.
+    pub fn check_locked_funds(
+        info: &MessageInfo,
+        locked: &cosmwasm_std::Coin,
+        fee_token: &str,
+        fee: u128,
+    ) -> Result<(), ContractError> {
+        let mut attached = info
+            .funds
+            .iter()
+            .find(|coin| coin.denom == locked.denom)
+            .map(|c| c.amount.u128())
+            .unwrap_or(0);
+        if locked.denom == fee_token {
+            attached = attached.saturating_sub(fee);
+        }
+
+        if attached != locked.amount.u128() {
+            return Err(ContractError::IncorrectEscrowAmount(
+                locked.amount.u128(),
+                attached,
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn calculate_fee(deps: Deps, target_chain: String) -> Result<u128, ContractError> {
         let fee_factor = read_state(deps.storage, |state| {
             state.fee_token_factor.ok_or(ContractError::FeeHasNotSet)
         })?;
@@ -899,56 +2753,178 @@ pub mod execute {
         })?;
         Ok(fee_factor * chain_factor)
     }
+
+    pub fn parse_amount(amount: &str) -> Result<u128, ContractError> {
+        amount
+            .parse::<u128>()
+            .map_err(|_| ContractError::InvalidAmount(amount.to_string()))
+    }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::GetState {} => to_json_binary(&read_state(deps.storage, |state| state.clone())),
         QueryMsg::GetTokenList {} => to_json_binary(&query::get_token_list(deps)?),
+        QueryMsg::GetToken { token_id } => to_json_binary(&query::get_token(deps, token_id)?),
+        QueryMsg::GetTokenDenom { token_id } => {
+            to_json_binary(&query::get_token_denom(deps, token_id)?)
+        }
+        QueryMsg::GetClaimable { address } => to_json_binary(&query::get_claimable(deps, address)?),
+        QueryMsg::GetForward { channel, sequence } => {
+            to_json_binary(&query::get_forward(deps, channel, sequence)?)
+        }
+        QueryMsg::GetRelayer { relayer } => to_json_binary(&query::get_relayer(deps, relayer)?),
+        QueryMsg::GetRelayerLeaderboard { limit } => {
+            to_json_binary(&query::get_relayer_leaderboard(deps, limit)?)
+        }
+        QueryMsg::GetMintAttestations { ticket_id } => {
+            to_json_binary(&query::get_mint_attestations(deps, ticket_id)?)
+        }
+        QueryMsg::GetPendingOperation { id } => {
+            to_json_binary(&query::get_pending_operation(deps, id)?)
+        }
+        QueryMsg::GetPendingOperations { start_after, limit } => {
+            to_json_binary(&query::get_pending_operations(deps, start_after, limit)?)
+        }
+        QueryMsg::GetArchivedToken { token_id } => {
+            to_json_binary(&query::get_archived_token(deps, token_id)?)
+        }
+        QueryMsg::GetTokenMetadataHistory {
+            token_id,
+            start_after,
+            limit,
+        } => to_json_binary(&query::get_token_metadata_history(
+            deps,
+            token_id,
+            start_after,
+            limit,
+        )?),
         QueryMsg::GetFeeInfo {} => to_json_binary(&query::get_fee_info(deps)?),
         QueryMsg::GetTargetChainFee { target_chain } => {
-            let fee_info = query::get_fee_info(deps)?;
-            if fee_info.fee_token.is_none() {
-                return to_json_binary(&GetTargetChainFeeResponse {
-                    target_chain: target_chain,
-                    fee_token: None,
-                    fee_token_factor: None,
-                    fee_amount: None,
-                });
-            }
-            let fee_token = fee_info.fee_token.unwrap();
-            let fee_token_factor = fee_info.fee_token_factor.unwrap();
-
-            let fee_factor = read_state(deps.storage, |state| {
-                state.fee_token_factor.ok_or(ContractError::FeeHasNotSet)
-            })
-            .unwrap();
-            let chain_factor = read_state(deps.storage, |state| {
-                state
-                    .target_chain_factor
-                    .get(&target_chain)
-                    .cloned()
-                    .ok_or(ContractError::FeeHasNotSet)
-            })
-            .unwrap();
-            let fee_amount = fee_factor * chain_factor;
-
-            to_json_binary(&GetTargetChainFeeResponse {
-                target_chain: target_chain,
-                fee_token: Some(fee_token),
-                fee_token_factor: Some(fee_token_factor),
-                fee_amount: Some(fee_amount),
-            })
+            to_json_binary(&query::get_target_chain_fee(deps, target_chain)?)
+        }
+        QueryMsg::QuoteRedeem {
+            token_id,
+            receiver,
+            target_chain,
+            amount,
+            sender,
+        } => to_json_binary(&query::quote_redeem(
+            deps,
+            &env,
+            token_id,
+            receiver,
+            target_chain,
+            amount,
+            sender,
+        )?),
+        QueryMsg::GetReachableChains { token_id } => {
+            to_json_binary(&query::get_reachable_chains(deps, token_id)?)
+        }
+        QueryMsg::GetApproval { sender, operator } => {
+            to_json_binary(&query::get_approval(deps, sender, operator)?)
+        }
+        QueryMsg::GetApprovals { sender } => to_json_binary(&query::get_approvals(deps, sender)?),
+        QueryMsg::SimulateRedeem {
+            sender,
+            funds,
+            token_id,
+            receiver,
+            amount,
+            target_chain,
+        } => {
+            execute::check_chain_active(deps).map_err(query::simulation_error)?;
+            let info = query::simulated_info(deps, sender, funds)?;
+            let (_, req) = execute::prepare_redeem_token(
+                deps,
+                &env,
+                &info,
+                token_id,
+                receiver,
+                amount,
+                target_chain,
+            )
+            .map_err(query::simulation_error)?;
+            to_json_binary(&req)
+        }
+        QueryMsg::SimulateRedeemAllBTC {
+            sender,
+            funds,
+            receiver,
+            amount,
+            target_chain,
+        } => {
+            execute::check_chain_active(deps).map_err(query::simulation_error)?;
+            let info = query::simulated_info(deps, sender, funds)?;
+            let (_, redeem_allbtc) =
+                execute::prepare_redeem_allbtc(deps, &env, &info, receiver, amount, target_chain)
+                    .map_err(query::simulation_error)?;
+            let seq = read_state(deps.storage, |s| s.generate_ticket_sequence);
+            to_json_binary(&execute::redeem_allbtc_ticket_req(
+                deps.storage,
+                &env,
+                seq,
+                redeem_allbtc,
+            ))
+        }
+        QueryMsg::SimulateGenerateTicket {
+            signer,
+            funds,
+            token_id,
+            sender,
+            receiver,
+            amount,
+            target_chain,
+            action,
+            memo,
+        } => {
+            execute::check_chain_active(deps).map_err(query::simulation_error)?;
+            let info = query::simulated_info(deps, signer, funds)?;
+            let (_, req) = execute::prepare_generate_ticket(
+                deps,
+                &env,
+                &info,
+                TicketParams {
+                    token_id,
+                    sender,
+                    receiver,
+                    amount,
+                    target_chain,
+                    action,
+                    memo,
+                    from_hook: false,
+                },
+            )
+            .map_err(query::simulation_error)?;
+            to_json_binary(&req)
         }
     }
 }
 
 pub mod query {
     use crate::{
-        msg::{GetFeeResponse, GetTokenResponse},
-        state::read_state,
+        contract::execute::{
+            calculate_fee, check_chain_active, check_target_chain, check_token_route,
+            parse_amount, prepare_redeem_token,
+        },
+        forward::{claimable, Forward, FORWARDS},
+        msg::{
+            GetFeeResponse, GetTargetChainFeeResponse, GetTokenResponse, QuoteRedeemResponse,
+            RedeemCheckError,
+        },
+        route::Token,
+        state::{
+            read_state, Approval, ArchivedToken, MintAttestation, PendingOperation, RelayerStats,
+            TokenMetadataChange, APPROVALS, ARCHIVED_TOKENS, MINT_ATTESTATIONS, PENDING_OPERATIONS,
+            RELAYERS, RELAYER_RANKING, TOKEN_METADATA_HISTORY,
+        },
     };
+    use cosmwasm_std::{Coin, Order, Uint128};
+    use cw_storage_plus::Bound;
+
+    const DEFAULT_LIMIT: u32 = 30;
+    const MAX_LIMIT: u32 = 100;
 
     use super::*;
 
@@ -963,6 +2939,101 @@ pub mod query {
         Ok(GetTokenResponse { tokens })
     }
 
+    pub fn get_token(deps: Deps, token_id: String) -> StdResult<Option<Token>> {
+        let token_id = read_state(deps.storage, |s| s.token_ids.local_id(&token_id));
+        Ok(read_state(deps.storage, |state| {
+            state.tokens.get(&token_id).cloned()
+        }))
+    }
+
+    pub fn get_archived_token(deps: Deps, token_id: String) -> StdResult<Option<ArchivedToken>> {
+        let token_id = read_state(deps.storage, |s| s.token_ids.local_id(&token_id));
+        ARCHIVED_TOKENS.may_load(deps.storage, &token_id)
+    }
+
+    pub fn get_token_denom(deps: Deps, token_id: String) -> StdResult<Option<String>> {
+        Ok(read_state(deps.storage, |s| {
+            s.token_denom(&s.token_ids.local_id(&token_id)).ok()
+        }))
+    }
+
+    pub fn get_token_metadata_history(
+        deps: Deps,
+        token_id: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<TokenMetadataChange>> {
+        let token_id = read_state(deps.storage, |s| s.token_ids.local_id(&token_id));
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        TOKEN_METADATA_HISTORY
+            .prefix(&token_id)
+            .range(
+                deps.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|item| item.map(|(_, change)| change))
+            .collect()
+    }
+
+    pub fn get_claimable(deps: Deps, address: String) -> StdResult<Vec<cosmwasm_std::Coin>> {
+        let address = deps.api.addr_validate(&address)?;
+        claimable(deps.storage, &address)
+    }
+
+    pub fn get_forward(deps: Deps, channel: String, sequence: u64) -> StdResult<Option<Forward>> {
+        FORWARDS.may_load(deps.storage, (&channel, sequence))
+    }
+
+    pub fn get_relayer(deps: Deps, relayer: String) -> StdResult<Option<RelayerStats>> {
+        let relayer = deps.api.addr_validate(&relayer)?;
+        RELAYERS.may_load(deps.storage, &relayer)
+    }
+
+    pub fn get_relayer_leaderboard(deps: Deps, limit: Option<u32>) -> StdResult<Vec<RelayerStats>> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        RELAYER_RANKING
+            .keys(deps.storage, None, None, Order::Descending)
+            .take(limit)
+            .map(|key| {
+                let (_, relayer) = key?;
+                RELAYERS.load(deps.storage, &relayer)
+            })
+            .collect()
+    }
+
+    pub fn get_mint_attestations(deps: Deps, ticket_id: String) -> StdResult<Vec<MintAttestation>> {
+        MINT_ATTESTATIONS
+            .prefix(&ticket_id)
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, attestation)| attestation))
+            .collect()
+    }
+
+    pub fn get_pending_operation(deps: Deps, id: u64) -> StdResult<Option<PendingOperation>> {
+        PENDING_OPERATIONS.may_load(deps.storage, id)
+    }
+
+    pub fn get_pending_operations(
+        deps: Deps,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<PendingOperation>> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        PENDING_OPERATIONS
+            .range(
+                deps.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|item| item.map(|(_, operation)| operation))
+            .collect()
+    }
+
     pub fn get_fee_info(deps: Deps) -> StdResult<GetFeeResponse> {
         Ok(read_state(deps.storage, |state| GetFeeResponse {
             fee_token: state.fee_token.clone(),
@@ -970,4 +3041,204 @@ pub mod query {
             target_chain_factor: state.target_chain_factor.clone(),
         }))
     }
+
+    pub fn get_reachable_chains(deps: Deps, token_id: String) -> StdResult<Vec<String>> {
+        read_state(deps.storage, |s| {
+            let token_id = s.token_ids.local_id(&token_id);
+            if !s.tokens.contains_key(&token_id) {
+                return Err(simulation_error(ContractError::TokenNotFound));
+            }
+            Ok(s.counterparties
+                .keys()
+                .filter(|chain_id| {
+                    s.check_target_chain(chain_id).is_ok()
+                        && s.token_routes
+                            .get(&token_id)
+                            .map_or(true, |routes| routes.permits(chain_id))
+                })
+                .cloned()
+                .collect())
+        })
+    }
+
+    pub fn get_approval(
+        deps: Deps,
+        sender: String,
+        operator: String,
+    ) -> StdResult<Option<Approval>> {
+        let sender = deps.api.addr_validate(&sender)?;
+        let operator = deps.api.addr_validate(&operator)?;
+        APPROVALS.may_load(deps.storage, (&sender, &operator))
+    }
+
+    pub fn get_approvals(deps: Deps, sender: String) -> StdResult<Vec<Approval>> {
+        let sender = deps.api.addr_validate(&sender)?;
+        APPROVALS
+            .prefix(&sender)
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, approval)| approval))
+            .collect()
+    }
+
+    pub fn simulated_info(deps: Deps, sender: String, funds: Vec<Coin>) -> StdResult<MessageInfo> {
+        Ok(MessageInfo {
+            sender: deps.api.addr_validate(&sender)?,
+            funds,
+        })
+    }
+
+    pub fn simulation_error(err: ContractError) -> StdError {
+        match err {
+            ContractError::Std(e) => e,
+            e => StdError::generic_err(e.to_string()),
+        }
+    }
+
+    pub fn get_target_chain_fee(
+        deps: Deps,
+        target_chain: String,
+    ) -> StdResult<GetTargetChainFeeResponse> {
+        let (fee_token, fee_token_factor) =
+            read_state(deps.storage, |s| (s.fee_token.clone(), s.fee_token_factor));
+        // a missing factor means the fee is not configured yet, not a query failure
+        let fee_amount = fee_token
+            .as_ref()
+            .and_then(|_| calculate_fee(deps, target_chain.clone()).ok());
+
+        Ok(GetTargetChainFeeResponse {
+            target_chain,
+            fee_token,
+            fee_token_factor,
+            fee_amount,
+        })
+    }
+
+    /// Runs the same checks as `RedeemToken` without touching state, collecting every
+    /// failure instead of stopping at the first one. The redeem is then dry-run with the coins
+    /// it needs attached, so the quote never accepts a redeem that would fail.
+    pub fn quote_redeem(
+        deps: Deps,
+        env: &Env,
+        token_id: String,
+        receiver: String,
+        target_chain: String,
+        amount: String,
+        sender: String,
+    ) -> StdResult<QuoteRedeemResponse> {
+        let sender_addr = deps.api.addr_validate(&sender)?;
+        let local_id = read_state(deps.storage, |s| s.token_ids.local_id(&token_id));
+        let mut errors = vec![];
+
+        let chain_active = read_state(deps.storage, |s| s.chain_state == ChainState::Active);
+        if !chain_active {
+            errors.push(RedeemCheckError::ChainDeactive);
+        }
+
+        let token_exists = read_state(deps.storage, |s| s.tokens.contains_key(&local_id));
+        if !token_exists {
+            errors.push(RedeemCheckError::TokenNotFound);
+        }
+
+        if let Err(e) = check_target_chain(deps, target_chain.clone()) {
+            errors.push(e.into());
+        }
+        if let Err(e) = check_token_route(deps, &local_id, &target_chain) {
+            errors.push(e.into());
+        }
+
+        let fee_token = read_state(deps.storage, |s| s.fee_token.clone());
+        let fee_amount = match fee_token {
+            Some(_) => match calculate_fee(deps, target_chain.clone()) {
+                Ok(fee) => Some(fee),
+                Err(e) => {
+                    errors.push(e.into());
+                    None
+                }
+            },
+            None => {
+                errors.push(RedeemCheckError::FeeHasNotSet);
+                None
+            }
+        };
+
+        let min_amount = read_state(deps.storage, |s| {
+            s.target_chain_redeem_min_amount
+                .get(&(local_id.clone(), target_chain.clone()))
+                .cloned()
+                .unwrap_or("0".to_string())
+        });
+        match (parse_amount(&amount), parse_amount(&min_amount)) {
+            (Ok(requested), Ok(min)) if requested < min => {
+                errors.push(RedeemCheckError::RedeemAmountLessThanMinAmount {
+                    min_amount: min_amount.clone(),
+                })
+            }
+            (Err(e), _) | (_, Err(e)) => errors.push(e.into()),
+            _ => {}
+        }
+
+        let mut balance = None;
+        if let Ok(denom) = read_state(deps.storage, |s| s.token_denom(&local_id)) {
+            let available = deps.querier.query_balance(sender.clone(), denom)?.amount;
+            if let Ok(amount) = parse_amount(&amount) {
+                if available.u128() < amount {
+                    errors.push(RedeemCheckError::InsufficientBalance {
+                        balance: available.to_string(),
+                    });
+                }
+            }
+            balance = Some(available.to_string());
+        }
+
+        // the redeem itself, paying the quoted fee and attaching the coins an escrowed token
+        // locks
+        let mut funds: Vec<Coin> = fee_token
+            .clone()
+            .zip(fee_amount)
+            .filter(|(_, fee)| *fee > 0)
+            .map(|(denom, fee)| Coin::new(fee, denom))
+            .into_iter()
+            .collect();
+        let escrow_denom = read_state(deps.storage, |s| s.escrow_tokens.get(&local_id).cloned());
+        if let (Some(denom), Ok(locked)) = (escrow_denom, parse_amount(&amount)) {
+            match funds.iter_mut().find(|coin| coin.denom == denom) {
+                Some(coin) => coin.amount += Uint128::new(locked),
+                None => funds.push(Coin::new(locked, denom)),
+            }
+        }
+        let info = MessageInfo {
+            sender: sender_addr,
+            funds,
+        };
+        let redeem = check_chain_active(deps).and_then(|_| {
+            prepare_redeem_token(
+                deps,
+                env,
+                &info,
+                token_id,
+                receiver,
+                amount.clone(),
+                target_chain.clone(),
+            )
+        });
+        if let Err(e) = redeem {
+            let e = RedeemCheckError::from(e);
+            if !errors.contains(&e) {
+                errors.push(e);
+            }
+        }
+
+        Ok(QuoteRedeemResponse {
+            token_id: read_state(deps.storage, |s| s.token_ids.omnity_id(&local_id)),
+            target_chain,
+            amount,
+            sender,
+            fee_token,
+            fee_amount,
+            min_amount,
+            balance,
+            chain_active,
+            errors,
+        })
+    }
 }
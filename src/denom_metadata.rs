@@ -0,0 +1,212 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cosmos::bank::v1beta1::{DenomUnit, Metadata},
+    route::{metadata_key, Token},
+    ContractError,
+};
+
+/// Extra unit listed as a JSON array under `metadata_key::DENOM_UNITS`, next to the base
+/// denom and the symbol unit.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DisplayUnit {
+    pub denom: String,
+    pub exponent: u32,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+/// Bank metadata of the factory denom `base` of `token`. Every rule the bank module checks
+/// is checked here, so an invalid token fails with a clear error instead of a failed message.
+pub fn denom_metadata(base: String, token: &Token) -> Result<Metadata, ContractError> {
+    if token.name.trim().is_empty() {
+        return Err(invalid("name is blank"));
+    }
+    if token.symbol.trim().is_empty() {
+        return Err(invalid("symbol is blank"));
+    }
+    if !is_valid_denom(&base) {
+        return Err(invalid(format!("base denom {} is invalid", base)));
+    }
+    let uri_hash = token.uri_hash();
+    if !uri_hash.is_empty() && !is_sha256_hex(&uri_hash) {
+        return Err(invalid(format!(
+            "uri_hash {} is not a sha256 hex digest",
+            uri_hash
+        )));
+    }
+
+    let mut units = match token.metadata.get(metadata_key::DENOM_UNITS) {
+        Some(units) => serde_json::from_str::<Vec<DisplayUnit>>(units)
+            .map_err(|e| invalid(format!("{}: {}", metadata_key::DENOM_UNITS, e)))?,
+        None => vec![],
+    };
+    // a token without decimals is displayed in its base unit, the symbol becomes an alias.
+    // So is a token whose symbol can't name a denom unit, such as `OP` or `1INCH`
+    let symbol_alias = token.decimals == 0 || !is_valid_denom(&token.symbol);
+    let display = if symbol_alias {
+        base.clone()
+    } else {
+        units.push(DisplayUnit {
+            denom: token.symbol.clone(),
+            exponent: token.decimals as u32,
+            aliases: token.denom_aliases(),
+        });
+        token.symbol.clone()
+    };
+    units.sort_by_key(|unit| unit.exponent);
+
+    let mut base_aliases = vec![];
+    if symbol_alias {
+        base_aliases.push(token.symbol.clone());
+        base_aliases.extend(token.denom_aliases());
+    }
+    let mut denom_units = vec![DenomUnit {
+        denom: base.clone(),
+        exponent: 0,
+        aliases: base_aliases,
+    }];
+    denom_units.extend(units.into_iter().map(|unit| DenomUnit {
+        denom: unit.denom,
+        exponent: unit.exponent,
+        aliases: unit.aliases,
+    }));
+    validate_denom_units(&denom_units)?;
+
+    Ok(Metadata {
+        description: token.description(),
+        denom_units,
+        base,
+        display,
+        name: token.name.clone(),
+        symbol: token.symbol.clone(),
+        uri: token.icon.clone().unwrap_or_default(),
+        uri_hash,
+    })
+}
+
+fn validate_denom_units(denom_units: &[DenomUnit]) -> Result<(), ContractError> {
+    let mut denoms = BTreeSet::new();
+    for (i, unit) in denom_units.iter().enumerate() {
+        if !is_valid_denom(&unit.denom) {
+            return Err(invalid(format!("denom unit {} is invalid", unit.denom)));
+        }
+        if i > 0 && unit.exponent <= denom_units[i - 1].exponent {
+            return Err(invalid(format!(
+                "denom unit {} repeats exponent {}",
+                unit.denom, unit.exponent
+            )));
+        }
+        if !denoms.insert(unit.denom.as_str()) {
+            return Err(invalid(format!("denom unit {} is duplicated", unit.denom)));
+        }
+        let mut aliases = BTreeSet::new();
+        for alias in &unit.aliases {
+            if alias.trim().is_empty() || !aliases.insert(alias.as_str()) {
+                return Err(invalid(format!(
+                    "alias {:?} of {} is blank or duplicated",
+                    alias, unit.denom
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The bank module's denom rule: `[a-zA-Z][a-zA-Z0-9/:._-]{2,127}`.
+pub fn is_valid_denom(denom: &str) -> bool {
+    let mut chars = denom.chars();
+    (3..=128).contains(&denom.len())
+        && chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || "/:._-".contains(c))
+}
+
+fn is_sha256_hex(hash: &str) -> bool {
+    hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn invalid(reason: impl Into<String>) -> ContractError {
+    ContractError::InvalidDenomMetadata(reason.into())
+}
+
+#[cfg(test)]
+fn test_token(symbol: &str, decimals: u8) -> Token {
+    Token {
+        token_id: "Bitcoin-runes-HOPE.YOU.GET.RICH".to_string(),
+        name: "HOPE•YOU•GET•RICH".to_string(),
+        symbol: symbol.to_string(),
+        decimals,
+        icon: None,
+        metadata: Default::default(),
+    }
+}
+
+#[test]
+pub fn test_denom_metadata() {
+    let base = "factory/osmo1contract/Bitcoin-runes-HOPE.YOU.GET.RICH".to_string();
+    let mut token = test_token("RICH", 2);
+    token.metadata.insert(
+        metadata_key::DENOM_UNITS.to_string(),
+        r#"[{"denom":"centirich","exponent":1}]"#.to_string(),
+    );
+    token
+        .metadata
+        .insert(metadata_key::URI_HASH.to_string(), "ab".repeat(32));
+
+    let metadata = denom_metadata(base.clone(), &token).unwrap();
+    assert_eq!(metadata.display, "RICH");
+    assert_eq!(metadata.uri_hash, "ab".repeat(32));
+    assert_eq!(
+        metadata
+            .denom_units
+            .iter()
+            .map(|unit| (unit.denom.as_str(), unit.exponent))
+            .collect::<Vec<_>>(),
+        vec![(base.as_str(), 0), ("centirich", 1), ("RICH", 2)]
+    );
+
+    let metadata = denom_metadata(base.clone(), &test_token("RICH", 0)).unwrap();
+    assert_eq!(metadata.display, base);
+    assert_eq!(metadata.denom_units.len(), 1);
+    assert_eq!(metadata.denom_units[0].aliases, vec!["RICH".to_string()]);
+}
+
+#[test]
+pub fn test_symbol_alias() {
+    let base = "factory/osmo1contract/Bitcoin-runes-HOPE.YOU.GET.RICH".to_string();
+    // too short or not starting with a letter, the symbol can't be a denom unit
+    for symbol in ["OP", "1INCH", "₿TC"] {
+        let metadata = denom_metadata(base.clone(), &test_token(symbol, 8)).unwrap();
+        assert_eq!(metadata.symbol, symbol);
+        assert_eq!(metadata.display, base);
+        assert_eq!(metadata.denom_units.len(), 1);
+        assert_eq!(metadata.denom_units[0].aliases, vec![symbol.to_string()]);
+    }
+}
+
+#[test]
+pub fn test_invalid_denom_metadata() {
+    let base = "factory/osmo1contract/Bitcoin-runes-HOPE.YOU.GET.RICH".to_string();
+    for symbol in ["", " "] {
+        assert!(
+            denom_metadata(base.clone(), &test_token(symbol, 8)).is_err(),
+            "{:?}",
+            symbol
+        );
+    }
+
+    let mut token = test_token("RICH", 2);
+    token.metadata.insert(
+        metadata_key::DENOM_UNITS.to_string(),
+        r#"[{"denom":"centirich","exponent":2}]"#.to_string(),
+    );
+    assert!(denom_metadata(base.clone(), &token).is_err());
+
+    let mut token = test_token("RICH", 2);
+    token
+        .metadata
+        .insert(metadata_key::URI_HASH.to_string(), "not a hash".to_string());
+    assert!(denom_metadata(base, &token).is_err());
+}
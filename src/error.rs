@@ -56,6 +56,95 @@ pub enum ContractError {
     #[error("ChainDeactive")]
     ChainDeactive,
 
+    #[error("InvalidAmount: {0}")]
+    InvalidAmount(String),
+
+    #[error("TokenNotEscrowed")]
+    TokenNotEscrowed,
+
+    #[error("EscrowTokenRequiresTransfer")]
+    EscrowTokenRequiresTransfer,
+
+    #[error("IncorrectEscrowAmount, required: {0}, attach: {1}")]
+    IncorrectEscrowAmount(u128, u128),
+
+    #[error("InsufficientEscrow, locked: {0}, release: {1}")]
+    InsufficientEscrow(u128, u128),
+
+    #[error("SenderNotApproved")]
+    SenderNotApproved,
+
+    #[error("ApprovalNotFound")]
+    ApprovalNotFound,
+
+    #[error("InvalidReceiver: {0}")]
+    InvalidReceiver(String),
+
+    #[error("TargetChainUnreachable")]
+    TargetChainUnreachable,
+
+    #[error("TokenRouteNotAllowed")]
+    TokenRouteNotAllowed,
+
+    #[error("InvalidDenomMetadata: {0}")]
+    InvalidDenomMetadata(String),
+
+    #[error("DecimalsLocked")]
+    DecimalsLocked,
+
+    #[error("TokenIdConflict: {0} and {1}")]
+    TokenIdConflict(String, String),
+
+    #[error("MemoTooLong: {0} bytes, max {1}")]
+    MemoTooLong(usize, u32),
+
+    #[error("InvalidMemo: {0}")]
+    InvalidMemo(String),
+
+    #[error("InvalidDenom: {0}")]
+    InvalidDenom(String),
+
+    #[error("NotDenomAdmin: {0}")]
+    NotDenomAdmin(String),
+
+    #[error("TokenRemoved")]
+    TokenRemoved,
+
+    #[error("SunsetNotOver, redeems close at {0}")]
+    SunsetNotOver(u64),
+
+    #[error("TokenNotMinted")]
+    TokenNotMinted,
+
+    #[error("InvalidAttestors: {0}")]
+    InvalidAttestors(String),
+
+    #[error("InvalidHubKey: {0}")]
+    InvalidHubKey(String),
+
+    #[error("InvalidSignature: {0}")]
+    InvalidSignature(String),
+
+    #[error("InvalidIbcChannel: {0}")]
+    InvalidIbcChannel(String),
+
+    #[error("NoClaimableBalance")]
+    NoClaimableBalance,
+
+    #[error("NoRelayRewards")]
+    NoRelayRewards,
+
+    #[error("OperationNotFound")]
+    OperationNotFound,
+
+    #[error("OperationLocked, executable at {0}")]
+    OperationLocked(u64),
+
+    #[error("TimelockTooShort, at least {0} seconds")]
+    TimelockTooShort(u64),
+
+    #[error("InsufficientRefundable, available: {0}, refund: {1}")]
+    InsufficientRefundable(u128, u128),
 }
 
 impl From<semver::Error> for ContractError {
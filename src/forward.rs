@@ -0,0 +1,322 @@
+use bech32::Bech32;
+use cosmwasm_std::{
+    Addr, Attribute, Coin, Env, Event, IbcMsg, IbcTimeout, Order, Reply, Response, StdResult,
+    Storage, SubMsg, SubMsgResult,
+};
+use cw_storage_plus::Map;
+use prost::Message;
+
+use crate::{cw_serde, msg::reply_msg_id, ContractError};
+
+/// Forwarded tokens the destination has not received by then come back and become claimable.
+pub const TRANSFER_TIMEOUT_SECS: u64 = 60 * 60;
+
+// forwards awaiting their ibc-hooks callback, by source channel and packet sequence
+pub const FORWARDS: Map<(&str, u64), Forward> = Map::new("ibc-forwards");
+// coins of failed forwards, claimable by the beneficiary
+pub const CLAIMABLE: Map<(&Addr, &str), u128> = Map::new("claimable");
+// claimable coins of each denom over all beneficiaries, not the contract's to spend
+pub const CLAIMABLE_TOTALS: Map<&str, u128> = Map::new("claimable-totals");
+
+/// Response of `/ibc.applications.transfer.v1.MsgTransfer`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MsgTransferResponse {
+    #[prost(uint64, tag = "1")]
+    pub sequence: u64,
+}
+
+/// Minted tokens sent on to a receiver on another chain with an ICS-20 transfer.
+#[cw_serde]
+pub struct Forward {
+    pub ticket_id: String,
+    pub channel: String,
+    // address on the destination chain
+    pub receiver: String,
+    // the receiver's account on this chain, credited if the transfer fails
+    pub beneficiary: Addr,
+    pub amount: Coin,
+}
+
+// sudo message ibc-hooks sends for transfers with an `ibc_callback` memo
+#[cw_serde]
+pub enum IbcLifecycleComplete {
+    IbcAck {
+        channel: String,
+        sequence: u64,
+        ack: String,
+        success: bool,
+    },
+    IbcTimeout {
+        channel: String,
+        sequence: u64,
+    },
+}
+
+/// Account a failed forward to `receiver` is credited to: `recovery` when the ticket names one,
+/// otherwise the receiver's local account. Nobody holds the key of a contract, so a contract
+/// receiver needs a recovery account.
+pub fn forward_beneficiary(
+    contract_addr: &Addr,
+    receiver: &str,
+    recovery: Option<&Addr>,
+) -> Result<Addr, ContractError> {
+    if let Some(recovery) = recovery {
+        return Ok(recovery.clone());
+    }
+    let beneficiary = local_account(contract_addr, receiver)?;
+    let (_, data) =
+        bech32::decode(receiver).map_err(|e| ContractError::InvalidReceiver(e.to_string()))?;
+    if data.len() != 20 {
+        return Err(ContractError::InvalidReceiver(format!(
+            "{} is not an account, forwards to it need a recovery account",
+            receiver
+        )));
+    }
+    Ok(beneficiary)
+}
+
+/// Account of `receiver` on this chain: the same address bytes with the contract's prefix, so
+/// the key controlling the receiver can claim a failed forward.
+pub fn local_account(contract_addr: &Addr, receiver: &str) -> Result<Addr, ContractError> {
+    let (hrp, _) = bech32::decode(contract_addr.as_str())
+        .map_err(|e| ContractError::CustomError(e.to_string()))?;
+    let (_, data) =
+        bech32::decode(receiver).map_err(|e| ContractError::InvalidReceiver(e.to_string()))?;
+    let account = bech32::encode::<Bech32>(hrp, &data)
+        .map_err(|e| ContractError::InvalidReceiver(e.to_string()))?;
+    Ok(Addr::unchecked(account))
+}
+
+/// Transfer of a forward, asking ibc-hooks to call the contract back with its outcome.
+pub fn forward_msg(env: &Env, forward: &Forward) -> Result<SubMsg, ContractError> {
+    let transfer = IbcMsg::Transfer {
+        channel_id: forward.channel.clone(),
+        to_address: forward.receiver.clone(),
+        amount: forward.amount.clone(),
+        timeout: IbcTimeout::with_timestamp(env.block.time.plus_seconds(TRANSFER_TIMEOUT_SECS)),
+        memo: Some(format!(r#"{{"ibc_callback":"{}"}}"#, env.contract.address)),
+    };
+    Ok(
+        SubMsg::reply_always(transfer, reply_msg_id::IBC_FORWARD_REPLY_ID).with_payload(
+            serde_json::to_vec(forward).map_err(|e| ContractError::CustomError(e.to_string()))?,
+        ),
+    )
+}
+
+/// Records a sent forward under its packet sequence, or credits a transfer that failed.
+pub fn reply_forward(storage: &mut dyn Storage, msg: Reply) -> Result<Response, ContractError> {
+    let forward: Forward = serde_json::from_slice(msg.payload.as_slice())
+        .map_err(|e| ContractError::CustomError(e.to_string()))?;
+    match msg.result {
+        SubMsgResult::Ok(response) => {
+            #[allow(deprecated)]
+            let data = response
+                .msg_responses
+                .first()
+                .map(|response| response.value.clone())
+                .or(response.data)
+                .unwrap_or_default();
+            let sequence = MsgTransferResponse::decode(data.as_slice())
+                .map_err(|e| ContractError::ReplyError(e.to_string()))?
+                .sequence;
+            FORWARDS.save(storage, (&forward.channel, sequence), &forward)?;
+            Ok(
+                Response::new().add_event(Event::new("TokenForwarded").add_attributes(vec![
+                    Attribute::new("ticket_id", forward.ticket_id),
+                    Attribute::new("channel", forward.channel),
+                    Attribute::new("sequence", sequence.to_string()),
+                    Attribute::new("receiver", forward.receiver),
+                    Attribute::new("amount", forward.amount.to_string()),
+                ])),
+            )
+        }
+        SubMsgResult::Err(err) => credit_forward(storage, forward, &err),
+    }
+}
+
+pub fn ibc_lifecycle_complete(
+    storage: &mut dyn Storage,
+    msg: IbcLifecycleComplete,
+) -> Result<Response, ContractError> {
+    let (channel, sequence, failure) = match msg {
+        IbcLifecycleComplete::IbcAck {
+            channel,
+            sequence,
+            ack,
+            success,
+        } => (channel, sequence, (!success).then_some(ack)),
+        IbcLifecycleComplete::IbcTimeout { channel, sequence } => {
+            (channel, sequence, Some("timeout".to_string()))
+        }
+    };
+    // only forwards ask for callbacks
+    let Some(forward) = FORWARDS.may_load(storage, (&channel, sequence))? else {
+        return Ok(Response::new());
+    };
+    FORWARDS.remove(storage, (&channel, sequence));
+
+    match failure {
+        Some(reason) => credit_forward(storage, forward, &reason),
+        None => Ok(
+            Response::new().add_event(Event::new("ForwardDelivered").add_attributes(vec![
+                Attribute::new("ticket_id", forward.ticket_id),
+                Attribute::new("channel", channel),
+                Attribute::new("sequence", sequence.to_string()),
+            ])),
+        ),
+    }
+}
+
+fn credit_forward(
+    storage: &mut dyn Storage,
+    forward: Forward,
+    reason: &str,
+) -> Result<Response, ContractError> {
+    CLAIMABLE.update(
+        storage,
+        (&forward.beneficiary, &forward.amount.denom),
+        |balance| -> StdResult<_> {
+            Ok(balance.unwrap_or_default() + forward.amount.amount.u128())
+        },
+    )?;
+    CLAIMABLE_TOTALS.update(storage, &forward.amount.denom, |total| -> StdResult<_> {
+        Ok(total.unwrap_or_default() + forward.amount.amount.u128())
+    })?;
+    Ok(
+        Response::new().add_event(Event::new("ForwardFailed").add_attributes(vec![
+            Attribute::new("ticket_id", forward.ticket_id),
+            Attribute::new("beneficiary", forward.beneficiary),
+            Attribute::new("amount", forward.amount.to_string()),
+            Attribute::new("reason", reason),
+        ])),
+    )
+}
+
+/// Takes every claimable coin of `beneficiary` out of the ledger.
+pub fn take_claimable(
+    storage: &mut dyn Storage,
+    beneficiary: &Addr,
+) -> Result<Vec<Coin>, ContractError> {
+    let coins = claimable(storage, beneficiary)?;
+    for coin in &coins {
+        CLAIMABLE.remove(storage, (beneficiary, &coin.denom));
+        CLAIMABLE_TOTALS.update(storage, &coin.denom, |total| -> StdResult<_> {
+            Ok(total.unwrap_or_default().saturating_sub(coin.amount.u128()))
+        })?;
+    }
+    Ok(coins)
+}
+
+pub fn claimable(storage: &dyn Storage, beneficiary: &Addr) -> StdResult<Vec<Coin>> {
+    CLAIMABLE
+        .prefix(beneficiary)
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(denom, amount)| Coin::new(amount, denom)))
+        .collect()
+}
+
+#[test]
+pub fn test_forward_lifecycle() {
+    use cosmwasm_std::testing::{mock_env, MockApi, MockStorage};
+    use cosmwasm_std::{Binary, MsgResponse, SubMsgResponse};
+
+    let mut storage = MockStorage::new();
+    let mut env = mock_env();
+    env.contract.address = MockApi::default().addr_make("contract");
+    let receiver = "cosmos1qypqxpqpqgpsgqgzqvzqzqsrqsqsyqcyy2wt4y";
+    let beneficiary = local_account(&env.contract.address, receiver).unwrap();
+    assert!(beneficiary.as_str().starts_with("cosmwasm1"));
+    assert!(local_account(&env.contract.address, "receiver").is_err());
+    assert_eq!(
+        forward_beneficiary(&env.contract.address, receiver, None).unwrap(),
+        beneficiary
+    );
+    // nobody can claim for a contract, it takes the recovery account
+    let contract_receiver = "cosmos14hj2tavq8fpesdwxxcu44rty3hh90vhujrvcmstl4zr3txmfvw9s4hmalr";
+    let recovery = MockApi::default().addr_make("recovery");
+    assert!(matches!(
+        forward_beneficiary(&env.contract.address, contract_receiver, None),
+        Err(ContractError::InvalidReceiver(e)) if e.contains("recovery account")
+    ));
+    assert_eq!(
+        forward_beneficiary(&env.contract.address, contract_receiver, Some(&recovery)).unwrap(),
+        recovery
+    );
+
+    let forward = |ticket_id: &str| Forward {
+        ticket_id: ticket_id.to_string(),
+        channel: "channel-0".to_string(),
+        receiver: receiver.to_string(),
+        beneficiary: beneficiary.clone(),
+        amount: Coin::new(100u128, "uosmo"),
+    };
+    let sent = |storage: &mut MockStorage, ticket_id: &str, sequence: u64| {
+        #[allow(deprecated)]
+        let result = SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: None,
+            msg_responses: vec![MsgResponse {
+                type_url: "/ibc.applications.transfer.v1.MsgTransferResponse".to_string(),
+                value: Binary::new(MsgTransferResponse { sequence }.encode_to_vec()),
+            }],
+        });
+        let payload = forward_msg(&env, &forward(ticket_id)).unwrap().payload;
+        reply_forward(
+            storage,
+            Reply {
+                id: reply_msg_id::IBC_FORWARD_REPLY_ID,
+                payload,
+                gas_used: 0,
+                result,
+            },
+        )
+        .unwrap();
+    };
+
+    sent(&mut storage, "delivered", 1);
+    sent(&mut storage, "timed-out", 2);
+    assert_eq!(
+        FORWARDS.load(&storage, ("channel-0", 2)).unwrap(),
+        forward("timed-out")
+    );
+    ibc_lifecycle_complete(
+        &mut storage,
+        IbcLifecycleComplete::IbcAck {
+            channel: "channel-0".to_string(),
+            sequence: 1,
+            ack: "".to_string(),
+            success: true,
+        },
+    )
+    .unwrap();
+    assert_eq!(claimable(&storage, &beneficiary).unwrap(), vec![]);
+    ibc_lifecycle_complete(
+        &mut storage,
+        IbcLifecycleComplete::IbcTimeout {
+            channel: "channel-0".to_string(),
+            sequence: 2,
+        },
+    )
+    .unwrap();
+    assert!(FORWARDS.is_empty(&storage));
+
+    // a transfer failing right away is credited too
+    let payload = forward_msg(&env, &forward("failed")).unwrap().payload;
+    reply_forward(
+        &mut storage,
+        Reply {
+            id: reply_msg_id::IBC_FORWARD_REPLY_ID,
+            payload,
+            gas_used: 0,
+            result: SubMsgResult::Err("channel not found".to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(CLAIMABLE_TOTALS.load(&storage, "uosmo").unwrap(), 200);
+    assert_eq!(
+        take_claimable(&mut storage, &beneficiary).unwrap(),
+        vec![Coin::new(200u128, "uosmo")]
+    );
+    assert_eq!(claimable(&storage, &beneficiary).unwrap(), vec![]);
+    assert_eq!(CLAIMABLE_TOTALS.load(&storage, "uosmo").unwrap(), 0);
+}
@@ -0,0 +1,82 @@
+use bech32::{Bech32, Hrp};
+use cosmwasm_std::{Addr, Coin};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{cw_serde, state::TxAction, ContractError};
+
+/// Prefix of the addresses ibc-hooks executes contracts from.
+const SENDER_PREFIX: &str = "ibc-wasm-hook-intermediary";
+
+/// Ticket requested by the wasm memo of an ICS-20 transfer, paid with the transferred coin.
+#[cw_serde]
+pub enum HookAction {
+    GenerateTicket {
+        token_id: String,
+        receiver: String,
+        amount: String,
+        target_chain: String,
+        action: TxAction,
+        memo: Option<String>,
+    },
+    RedeemToken {
+        token_id: String,
+        receiver: String,
+        amount: String,
+        target_chain: String,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HookRecovery {
+    pub channel: String,
+    pub original_sender: String,
+    pub recovery: Addr,
+    pub funds: Vec<Coin>,
+}
+
+/// Address ibc-hooks executes the memo of a transfer from: the bech32 encoding, with the
+/// contract's prefix, of `sha256(sha256(SENDER_PREFIX) ++ "{channel}/{original_sender}")`.
+/// `channel` is the channel of this chain the transfer arrived on.
+pub fn intermediate_sender(
+    contract_addr: &Addr,
+    channel: &str,
+    original_sender: &str,
+) -> Result<String, ContractError> {
+    let (hrp, _) = bech32::decode(contract_addr.as_str())
+        .map_err(|e| ContractError::CustomError(e.to_string()))?;
+    derive_sender(hrp, channel, original_sender)
+}
+
+fn derive_sender(hrp: Hrp, channel: &str, original_sender: &str) -> Result<String, ContractError> {
+    let mut hasher = Sha256::new();
+    hasher.update(Sha256::digest(SENDER_PREFIX));
+    hasher.update(format!("{}/{}", channel, original_sender));
+    bech32::encode::<Bech32>(hrp, &hasher.finalize())
+        .map_err(|e| ContractError::CustomError(e.to_string()))
+}
+
+#[test]
+pub fn test_intermediate_sender() {
+    let hrp = Hrp::parse("osmo").unwrap();
+    let contract = Addr::unchecked(bech32::encode::<Bech32>(hrp, &[7u8; 32]).unwrap());
+    let original_sender = "cosmos1qyqszqgpqyqszqgpqyqszqgpqyqszqgpjnp7du";
+
+    let sender = intermediate_sender(&contract, "channel-0", original_sender).unwrap();
+    assert_eq!(
+        sender,
+        "osmo16kv7q236wk7gzd5audmwm97l9gj45q334ay7gw8wxsda2tlznxqswe4dqy"
+    );
+    // bound to the arrival channel and the original sender
+    assert_ne!(
+        sender,
+        intermediate_sender(&contract, "channel-1", original_sender).unwrap()
+    );
+    assert_ne!(
+        sender,
+        intermediate_sender(&contract, "channel-0", "cosmos1other").unwrap()
+    );
+    assert!(
+        intermediate_sender(&Addr::unchecked("contract"), "channel-0", original_sender).is_err()
+    );
+}
@@ -0,0 +1,213 @@
+use cosmwasm_std::{Addr, Api, Binary};
+use sha2::{Digest, Sha256};
+
+use crate::{cw_serde, types::MintTicket, ContractError};
+
+/// Domains separating the kinds of messages signed by the hub key.
+const MINT_TICKET_DOMAIN: &str = "omnity-port-cosmos/mint-ticket";
+const DIRECTIVE_DOMAIN: &str = "omnity-port-cosmos/directive";
+
+#[cw_serde]
+pub enum SignatureScheme {
+    // signs the message itself
+    Ed25519,
+    // signs the sha256 digest of the message
+    Secp256k1,
+}
+
+impl Eq for SignatureScheme {}
+
+/// Public key of the Omnity hub, tickets it signs can be submitted by anyone.
+#[cw_serde]
+pub struct HubKey {
+    pub scheme: SignatureScheme,
+    pub public_key: Binary,
+}
+
+impl Eq for HubKey {}
+
+impl HubKey {
+    pub fn validate(&self) -> Result<(), ContractError> {
+        let valid = match self.scheme {
+            SignatureScheme::Ed25519 => self.public_key.len() == 32,
+            // compressed or uncompressed SEC1 encoding
+            SignatureScheme::Secp256k1 => matches!(self.public_key.len(), 33 | 65),
+        };
+        if !valid {
+            return Err(ContractError::InvalidHubKey(format!(
+                "{} bytes {:?} public key",
+                self.public_key.len(),
+                self.scheme
+            )));
+        }
+        Ok(())
+    }
+
+    /// Checks `signature` of `message` against the key with the chain's crypto api.
+    pub fn verify(
+        &self,
+        api: &dyn Api,
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<(), ContractError> {
+        let verified = match self.scheme {
+            SignatureScheme::Ed25519 => api.ed25519_verify(message, signature, &self.public_key),
+            SignatureScheme::Secp256k1 => {
+                api.secp256k1_verify(&Sha256::digest(message), signature, &self.public_key)
+            }
+        }
+        .map_err(|e| ContractError::InvalidSignature(e.to_string()))?;
+        if !verified {
+            return Err(ContractError::InvalidSignature(
+                "signature does not match the hub key".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Bytes the hub signs to authorize a mint on this port. The contract address and the chain
+/// id bind the signature to one deployment, the ticket id is only ever minted once. The
+/// channel and the recovery account of a forwarded ticket come last, so tickets without one
+/// sign the same bytes as before forwards existed.
+pub fn mint_ticket_message(contract_addr: &Addr, chain_id: &str, ticket: &MintTicket) -> Vec<u8> {
+    let mut fields = vec![
+        contract_addr.as_str(),
+        chain_id,
+        &ticket.ticket_id,
+        &ticket.token_id,
+        ticket.receiver.as_str(),
+        &ticket.amount,
+        ticket.transmuter.as_deref().unwrap_or_default(),
+    ];
+    fields.extend(ticket.channel.as_deref());
+    fields.extend(ticket.recovery.as_ref().map(Addr::as_str));
+    signed_message(MINT_TICKET_DOMAIN, &fields)
+}
+
+/// Bytes the hub signs to authorize a directive: the contract address and the chain id, the
+/// sequence and the directive exactly as encoded by the hub, so no re-encoding has to match.
+pub fn directive_message(
+    contract_addr: &Addr,
+    chain_id: &str,
+    seq: u64,
+    directive: &[u8],
+) -> Vec<u8> {
+    signed_message(
+        DIRECTIVE_DOMAIN,
+        &[
+            contract_addr.as_bytes(),
+            chain_id.as_bytes(),
+            seq.to_string().as_bytes(),
+            directive,
+        ],
+    )
+}
+
+/// `domain` followed by the length prefixed fields, so fields cannot be shifted into each
+/// other.
+pub fn signed_message<F: AsRef<[u8]>>(domain: &str, fields: &[F]) -> Vec<u8> {
+    let mut message = domain.as_bytes().to_vec();
+    for field in fields {
+        let field = field.as_ref();
+        message.extend((field.len() as u64).to_be_bytes());
+        message.extend(field);
+    }
+    message
+}
+
+#[cfg(test)]
+fn test_message() -> Vec<u8> {
+    mint_ticket_message(
+        &Addr::unchecked("osmo1port"),
+        "osmosis-1",
+        &MintTicket {
+            ticket_id: "ticket".to_string(),
+            token_id: "Bitcoin-runes-HOPE•YOU•GET•RICH".to_string(),
+            receiver: Addr::unchecked("osmo1receiver"),
+            amount: "100".to_string(),
+            transmuter: None,
+            channel: None,
+            recovery: None,
+        },
+    )
+}
+
+#[test]
+pub fn test_ed25519_hub_key() {
+    use cosmwasm_std::testing::MockApi;
+    use ed25519_zebra::{SigningKey, VerificationKey};
+
+    let signing_key = SigningKey::from([7u8; 32]);
+    let hub_key = HubKey {
+        scheme: SignatureScheme::Ed25519,
+        public_key: Binary::from(VerificationKey::from(&signing_key).as_ref()),
+    };
+    hub_key.validate().unwrap();
+
+    let message = test_message();
+    let signature = signing_key.sign(&message).to_bytes();
+    let api = MockApi::default();
+    hub_key.verify(&api, &message, &signature).unwrap();
+
+    let mut other = message.clone();
+    other.push(0);
+    assert!(matches!(
+        hub_key.verify(&api, &other, &signature),
+        Err(ContractError::InvalidSignature(_))
+    ));
+}
+
+#[test]
+pub fn test_directive_message() {
+    let port = Addr::unchecked("osmo1port");
+    let directive = br#"{"toggle_chain_state":{"chain_id":"Bitcoin","action":"deactivate"}}"#;
+    let message = directive_message(&port, "osmosis-1", 7, directive);
+    assert_eq!(message, directive_message(&port, "osmosis-1", 7, directive));
+    // bound to the deployment, the chain and the sequence
+    assert_ne!(
+        message,
+        directive_message(&Addr::unchecked("osmo1other"), "osmosis-1", 7, directive)
+    );
+    assert_ne!(message, directive_message(&port, "osmosis-1", 8, directive));
+    assert_ne!(message, directive_message(&port, "other", 7, directive));
+    assert!(message.ends_with(directive));
+}
+
+#[test]
+pub fn test_secp256k1_hub_key() {
+    use cosmwasm_std::testing::MockApi;
+    use k256::ecdsa::{signature::Signer, Signature, SigningKey};
+
+    let signing_key = SigningKey::from_bytes(&[7u8; 32].into()).unwrap();
+    let hub_key = HubKey {
+        scheme: SignatureScheme::Secp256k1,
+        public_key: Binary::from(
+            signing_key
+                .verifying_key()
+                .to_encoded_point(true)
+                .as_bytes(),
+        ),
+    };
+    hub_key.validate().unwrap();
+
+    // the signer hashes the message with sha256, like the contract
+    let message = test_message();
+    let signature: Signature = signing_key.sign(&message);
+    let api = MockApi::default();
+    hub_key
+        .verify(&api, &message, &signature.to_bytes())
+        .unwrap();
+    assert!(hub_key
+        .verify(&api, b"other", &signature.to_bytes())
+        .is_err());
+
+    let short = HubKey {
+        scheme: SignatureScheme::Secp256k1,
+        public_key: Binary::from([2u8; 32]),
+    };
+    assert!(matches!(
+        short.validate(),
+        Err(ContractError::InvalidHubKey(_))
+    ));
+}
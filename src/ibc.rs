@@ -0,0 +1,681 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    from_json, to_json_binary, Attribute, BankMsg, Binary, CosmosMsg, Deps, DepsMut, Env, Event,
+    Ibc3ChannelOpenResponse, IbcBasicResponse, IbcChannel, IbcChannelCloseMsg,
+    IbcChannelConnectMsg, IbcChannelOpenMsg, IbcChannelOpenResponse, IbcMsg, IbcOrder, IbcPacket,
+    IbcPacketAckMsg, IbcPacketReceiveMsg, IbcPacketTimeoutMsg, IbcReceiveResponse, IbcTimeout,
+    Response, Storage,
+};
+
+use crate::{
+    contract::execute::{
+        build_mint_msg, check_chain_active, check_hub_signature, check_mint, mint_ticket,
+        parse_amount, unlock_escrow,
+    },
+    cw_serde,
+    reply::generate_ticket_requested_event,
+    state::{read_state, GenerateTicketReq, GENERATE_TICKET_REQ, STATE},
+    types::MintTicket,
+    ContractError,
+};
+
+/// Version both ends of the channel must agree on.
+pub const IBC_VERSION: &str = "omnity-port-1";
+/// Tickets the counterpart has not received by then are refunded.
+pub const PACKET_TIMEOUT_SECS: u64 = 60 * 60;
+
+#[cw_serde]
+pub enum PortPacket {
+    // a ticket generated on this port, for the counterpart to hand to the hub
+    GenerateTicket(GenerateTicketReq),
+    // a ticket to mint on this port
+    MintToken(MintTokenPacket),
+}
+
+#[cw_serde]
+pub struct MintTokenPacket {
+    pub ticket_id: String,
+    pub token_id: String,
+    pub receiver: String,
+    pub amount: String,
+    pub transmuter: Option<String>,
+    // hub signature of the ticket, required once attestors are set
+    #[serde(default)]
+    pub signature: Option<Binary>,
+}
+
+// acknowledgement of a packet, in the usual `{"result":..}` or `{"error":..}` encoding
+#[cw_serde]
+pub enum PacketAck {
+    Result(Binary),
+    Error(String),
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_open(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelOpenMsg,
+) -> Result<IbcChannelOpenResponse, ContractError> {
+    check_channel(deps.storage, msg.channel(), msg.counterparty_version())?;
+    Ok(Some(Ibc3ChannelOpenResponse {
+        version: IBC_VERSION.to_string(),
+    }))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_connect(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelConnectMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let channel = msg.channel();
+    check_channel(deps.storage, channel, msg.counterparty_version())?;
+    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+        state.ibc_channel = Some(channel.endpoint.channel_id.clone());
+        Ok(state)
+    })?;
+
+    Ok(
+        IbcBasicResponse::new().add_event(Event::new("IbcChannelConnected").add_attributes(vec![
+            Attribute::new("channel_id", channel.endpoint.channel_id.clone()),
+            Attribute::new("port_id", channel.counterparty_endpoint.port_id.clone()),
+        ])),
+    )
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_close(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelCloseMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let channel_id = msg.channel().endpoint.channel_id.clone();
+    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+        if state.ibc_channel.as_ref() == Some(&channel_id) {
+            state.ibc_channel = None;
+        }
+        Ok(state)
+    })?;
+
+    Ok(IbcBasicResponse::new().add_event(
+        Event::new("IbcChannelClosed")
+            .add_attributes(vec![Attribute::new("channel_id", channel_id)]),
+    ))
+}
+
+/// Mints a ticket sent by the counterpart, which is trusted like the route unless attestors are
+/// set, then the ticket needs the hub signature. A ticket that can't be minted is acked with
+/// `PacketAck::Error` before anything of it is recorded, so the counterpart can refund it.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_receive(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketReceiveMsg,
+) -> Result<IbcReceiveResponse, ContractError> {
+    let (ticket, relayed) = match check_packet(deps.as_ref(), &env, &msg.packet) {
+        Ok(checked) => checked,
+        Err(err) => {
+            return Ok(
+                IbcReceiveResponse::new(to_json_binary(&PacketAck::Error(err.to_string()))?)
+                    .add_event(Event::new("MintPacketRejected").add_attributes(vec![
+                        Attribute::new("sequence", msg.packet.sequence.to_string()),
+                        Attribute::new("error", err.to_string()),
+                    ])),
+            )
+        }
+    };
+
+    let response = mint_ticket(deps, env, msg.relayer, ticket, None, relayed)?;
+    Ok(
+        IbcReceiveResponse::new(to_json_binary(&PacketAck::Result(Binary::default()))?)
+            .add_submessages(response.messages)
+            .add_events(response.events),
+    )
+}
+
+/// A ticket rejected by the counterpart never reaches the hub, it is refunded like a timeout.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_ack(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketAckMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let req = sent_ticket(&msg.original_packet)?;
+    match from_json(&msg.acknowledgement.data)? {
+        PacketAck::Result(_) => Ok(IbcBasicResponse::new().add_event(
+            Event::new("TicketDelivered")
+                .add_attributes(vec![Attribute::new("seq", req.seq.to_string())]),
+        )),
+        PacketAck::Error(error) => refund_ticket(deps, &env, req, &error),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_timeout(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketTimeoutMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let req = sent_ticket(&msg.packet)?;
+    refund_ticket(deps, &env, req, "timeout")
+}
+
+/// Records a generated ticket and hands it to the hub: as a packet to the counterpart if a
+/// channel is open, otherwise through the `GenerateTicketRequested` event the route relays. A
+/// packet is refunded when it fails, so it never gets the event as well.
+pub fn deliver_ticket(
+    storage: &mut dyn Storage,
+    env: &Env,
+    response: Response,
+    req: GenerateTicketReq,
+) -> Result<Response, ContractError> {
+    let Some(channel_id) = read_state(storage, |s| s.ibc_channel.clone()) else {
+        return Ok(response.add_event(generate_ticket_requested_event(storage, req)?));
+    };
+    GENERATE_TICKET_REQ.save(storage, req.seq, &req)?;
+    let event = Event::new("TicketPacketSent").add_attributes(vec![
+        Attribute::new("seq", req.seq.to_string()),
+        Attribute::new("channel_id", channel_id.clone()),
+    ]);
+    Ok(response
+        .add_message(IbcMsg::SendPacket {
+            channel_id,
+            data: to_json_binary(&PortPacket::GenerateTicket(req))?,
+            timeout: IbcTimeout::with_timestamp(env.block.time.plus_seconds(PACKET_TIMEOUT_SECS)),
+        })
+        .add_event(event))
+}
+
+/// Checks a received packet is a mint ticket that can be minted, without recording anything.
+/// The ticket comes back with whether it was signed by the hub.
+fn check_packet(
+    deps: Deps,
+    env: &Env,
+    packet: &IbcPacket,
+) -> Result<(MintTicket, bool), ContractError> {
+    // packets skip `execute`, so they are stopped with the chain here
+    check_chain_active(deps)?;
+    let channel = read_state(deps.storage, |s| s.ibc_channel.clone());
+    if channel.as_ref() != Some(&packet.dest.channel_id) {
+        return Err(ContractError::InvalidIbcChannel(format!(
+            "{} is not the open channel",
+            packet.dest.channel_id
+        )));
+    }
+    let packet = match from_json(&packet.data)? {
+        PortPacket::MintToken(packet) => packet,
+        PortPacket::GenerateTicket(_) => {
+            return Err(ContractError::InvalidIbcChannel(
+                "only mint tickets are received".to_string(),
+            ))
+        }
+    };
+    let ticket = MintTicket {
+        ticket_id: packet.ticket_id,
+        token_id: packet.token_id,
+        receiver: deps.api.addr_validate(&packet.receiver)?,
+        amount: packet.amount,
+        transmuter: packet.transmuter,
+        channel: None,
+        recovery: None,
+    };
+    match &packet.signature {
+        Some(signature) => check_hub_signature(deps, env, signature, &ticket)?,
+        // the counterpart stands in for the route, not for the attestors
+        None if read_state(deps.storage, |s| s.attestors.is_some()) => {
+            return Err(ContractError::InvalidSignature(
+                "mint packets need the hub signature while attestors are set".to_string(),
+            ))
+        }
+        None => {}
+    }
+    check_mint(deps, env, &ticket)?;
+    Ok((ticket, packet.signature.is_some()))
+}
+
+fn check_channel(
+    storage: &dyn Storage,
+    channel: &IbcChannel,
+    counterparty_version: Option<&str>,
+) -> Result<(), ContractError> {
+    let (port, open) = read_state(storage, |s| (s.ibc_port.clone(), s.ibc_channel.clone()));
+    let port = port.ok_or(ContractError::InvalidIbcChannel(
+        "no counterpart port is set".to_string(),
+    ))?;
+    // any chain may bind a port of that name, only the pinned connection reaches the counterpart
+    if port.connection_id != channel.connection_id {
+        return Err(ContractError::InvalidIbcChannel(format!(
+            "unexpected connection {}",
+            channel.connection_id
+        )));
+    }
+    if port.port_id != channel.counterparty_endpoint.port_id {
+        return Err(ContractError::InvalidIbcChannel(format!(
+            "unexpected counterpart port {}",
+            channel.counterparty_endpoint.port_id
+        )));
+    }
+    if let Some(open) = open.filter(|open| *open != channel.endpoint.channel_id) {
+        return Err(ContractError::InvalidIbcChannel(format!(
+            "{} is already open",
+            open
+        )));
+    }
+    if channel.order != IbcOrder::Unordered {
+        return Err(ContractError::InvalidIbcChannel(
+            "channel must be unordered".to_string(),
+        ));
+    }
+    for version in [Some(channel.version.as_str()), counterparty_version]
+        .into_iter()
+        .flatten()
+    {
+        if version != IBC_VERSION {
+            return Err(ContractError::InvalidIbcChannel(format!(
+                "version {}, expected {}",
+                version, IBC_VERSION
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn sent_ticket(packet: &IbcPacket) -> Result<GenerateTicketReq, ContractError> {
+    match from_json(&packet.data)? {
+        PortPacket::GenerateTicket(req) => Ok(req),
+        PortPacket::MintToken(_) => Err(ContractError::InvalidIbcChannel(
+            "only tickets are sent".to_string(),
+        )),
+    }
+}
+
+/// Gives a ticket that never reached the hub back to its sender, or to its refund account when
+/// the sender is of another chain: minted tokens are minted again, escrowed coins are released.
+/// The fee is kept and the ticket is forgotten.
+fn refund_ticket(
+    deps: DepsMut,
+    env: &Env,
+    req: GenerateTicketReq,
+    reason: &str,
+) -> Result<IbcBasicResponse, ContractError> {
+    let refund_to = deps
+        .api
+        .addr_validate(req.refund_to.as_deref().unwrap_or(&req.sender))?;
+    GENERATE_TICKET_REQ.remove(deps.storage, req.seq);
+    let token_id = read_state(deps.storage, |s| s.token_ids.local_id(&req.token_id));
+    let amount = parse_amount(&req.amount)?;
+    let escrow_denom = read_state(deps.storage, |s| s.escrow_tokens.get(&token_id).cloned());
+    let refund_msg = match escrow_denom {
+        Some(denom) => {
+            unlock_escrow(deps.storage, &token_id, amount)?;
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: refund_to.to_string(),
+                amount: vec![cosmwasm_std::Coin::new(amount, denom)],
+            })
+        }
+        None => build_mint_msg(
+            env.contract.address.clone(),
+            refund_to.to_string(),
+            read_state(deps.storage, |s| s.token_denom(&token_id))?,
+            req.amount.clone(),
+        ),
+    };
+
+    Ok(IbcBasicResponse::new().add_message(refund_msg).add_event(
+        Event::new("TicketRefunded").add_attributes(vec![
+            Attribute::new("seq", req.seq.to_string()),
+            Attribute::new("token_id", token_id),
+            Attribute::new("sender", req.sender),
+            Attribute::new("refund_to", refund_to),
+            Attribute::new("amount", req.amount),
+            Attribute::new("reason", reason),
+        ]),
+    ))
+}
+
+#[cfg(test)]
+const TOKEN_ID: &str = "osmosis-native-OSMO";
+
+/// Instantiates the port with an escrowed token holding 1000 locked uosmo, accepting channels
+/// from the counterpart port "their_port".
+#[cfg(test)]
+fn setup_escrow_port(mut deps: DepsMut, env: &Env) {
+    use crate::{contract::instantiate, msg::InstantiateMsg, route::Token, state::CounterpartPort};
+    use cosmwasm_std::testing::{message_info, MockApi};
+    use std::collections::HashMap;
+
+    let admin = MockApi::default().addr_make("admin");
+    let route = MockApi::default().addr_make("route");
+    instantiate(
+        deps.branch(),
+        env.clone(),
+        message_info(&admin, &[]),
+        InstantiateMsg {
+            route,
+            chain_id: "osmosis-1".to_string(),
+        },
+    )
+    .unwrap();
+    STATE
+        .update(deps.storage, |mut state| -> Result<_, ContractError> {
+            state.tokens.insert(
+                TOKEN_ID.to_string(),
+                Token {
+                    token_id: TOKEN_ID.to_string(),
+                    name: "OSMO".to_string(),
+                    symbol: "OSMO".to_string(),
+                    decimals: 6,
+                    icon: None,
+                    metadata: HashMap::new(),
+                },
+            );
+            state
+                .escrow_tokens
+                .insert(TOKEN_ID.to_string(), "uosmo".to_string());
+            state.escrow_balances.insert(TOKEN_ID.to_string(), 1000);
+            state.ibc_port = Some(CounterpartPort {
+                connection_id: "connection-2".to_string(),
+                port_id: "their_port".to_string(),
+            });
+            Ok(state)
+        })
+        .unwrap();
+}
+
+#[test]
+pub fn test_ibc_channel_handshake() {
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_env, mock_ibc_channel_close_confirm, mock_ibc_channel_connect_ack,
+        mock_ibc_channel_open_init, mock_ibc_channel_open_try,
+    };
+
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    setup_escrow_port(deps.as_mut(), &env);
+
+    let err = ibc_channel_open(
+        deps.as_mut(),
+        env.clone(),
+        mock_ibc_channel_open_init("channel-0", IbcOrder::Ordered, IBC_VERSION),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::InvalidIbcChannel(_)));
+    let err = ibc_channel_open(
+        deps.as_mut(),
+        env.clone(),
+        mock_ibc_channel_open_try("channel-0", IbcOrder::Unordered, "ics20-1"),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::InvalidIbcChannel(_)));
+    // the counterpart port on another connection is some other chain
+    let mut open = mock_ibc_channel_open_init("channel-0", IbcOrder::Unordered, IBC_VERSION);
+    if let IbcChannelOpenMsg::OpenInit { channel } = &mut open {
+        channel.connection_id = "connection-3".to_string();
+    }
+    let err = ibc_channel_open(deps.as_mut(), env.clone(), open).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidIbcChannel(_)));
+    let response = ibc_channel_open(
+        deps.as_mut(),
+        env.clone(),
+        mock_ibc_channel_open_init("channel-0", IbcOrder::Unordered, IBC_VERSION),
+    )
+    .unwrap();
+    assert_eq!(response.unwrap().version, IBC_VERSION);
+
+    ibc_channel_connect(
+        deps.as_mut(),
+        env.clone(),
+        mock_ibc_channel_connect_ack("channel-0", IbcOrder::Unordered, IBC_VERSION),
+    )
+    .unwrap();
+    assert_eq!(
+        read_state(&deps.storage, |s| s.ibc_channel.clone()),
+        Some("channel-0".to_string())
+    );
+    // a single channel to the counterpart
+    assert!(ibc_channel_open(
+        deps.as_mut(),
+        env.clone(),
+        mock_ibc_channel_open_init("channel-1", IbcOrder::Unordered, IBC_VERSION),
+    )
+    .is_err());
+
+    ibc_channel_close(
+        deps.as_mut(),
+        env,
+        mock_ibc_channel_close_confirm("channel-0", IbcOrder::Unordered, IBC_VERSION),
+    )
+    .unwrap();
+    assert_eq!(read_state(&deps.storage, |s| s.ibc_channel.clone()), None);
+}
+
+#[test]
+pub fn test_ibc_ticket_packets() {
+    use crate::{
+        route::ChainState,
+        state::{AttestorSet, TxAction},
+    };
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_env, mock_ibc_packet_ack, mock_ibc_packet_recv,
+        mock_ibc_packet_timeout,
+    };
+    use cosmwasm_std::{IbcAcknowledgement, SubMsg};
+
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    setup_escrow_port(deps.as_mut(), &env);
+    let sender = deps.api.addr_make("sender");
+    let receiver = deps.api.addr_make("receiver");
+    let req = GenerateTicketReq {
+        seq: 0,
+        target_chain_id: "Bitcoin".to_string(),
+        sender: sender.to_string(),
+        receiver: "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string(),
+        token_id: TOKEN_ID.to_string(),
+        amount: "300".to_string(),
+        action: TxAction::Transfer,
+        timestamp: env.block.time.nanos(),
+        block_height: env.block.height,
+        memo: None,
+        fee_token: "uosmo".to_string(),
+        fee_amount: "20".to_string(),
+        on_behalf_of: None,
+        refund_to: None,
+    };
+    // without a channel the route relays the ticket
+    let response = deliver_ticket(&mut deps.storage, &env, Response::new(), req.clone()).unwrap();
+    assert!(response.messages.is_empty());
+    assert_eq!(response.events[0].ty, "GenerateTicketRequested");
+    STATE
+        .update(&mut deps.storage, |mut state| -> Result<_, ContractError> {
+            state.ibc_channel = Some("channel-0".to_string());
+            Ok(state)
+        })
+        .unwrap();
+    // a packet is refunded when it fails, so the route must not relay it as well
+    let response = deliver_ticket(&mut deps.storage, &env, Response::new(), req.clone()).unwrap();
+    assert!(matches!(
+        &response.messages[0].msg,
+        CosmosMsg::Ibc(IbcMsg::SendPacket { channel_id, .. }) if channel_id == "channel-0"
+    ));
+    assert!(response
+        .events
+        .iter()
+        .all(|event| event.ty != "GenerateTicketRequested"));
+
+    let mint = MintTokenPacket {
+        ticket_id: "ticket".to_string(),
+        token_id: TOKEN_ID.to_string(),
+        receiver: receiver.to_string(),
+        amount: "100".to_string(),
+        transmuter: None,
+        signature: None,
+    };
+    let rejected = |response: IbcReceiveResponse| {
+        assert!(response.messages.is_empty());
+        matches!(
+            from_json(response.acknowledgement.unwrap()).unwrap(),
+            PacketAck::Error(_)
+        )
+    };
+    let response = ibc_packet_receive(
+        deps.as_mut(),
+        env.clone(),
+        mock_ibc_packet_recv("channel-1", &PortPacket::MintToken(mint.clone())).unwrap(),
+    )
+    .unwrap();
+    assert!(rejected(response));
+    let unknown = MintTokenPacket {
+        token_id: "osmosis-native-ATOM".to_string(),
+        ..mint.clone()
+    };
+    let response = ibc_packet_receive(
+        deps.as_mut(),
+        env.clone(),
+        mock_ibc_packet_recv("channel-0", &PortPacket::MintToken(unknown)).unwrap(),
+    )
+    .unwrap();
+    assert!(rejected(response));
+    let overdrawn = MintTokenPacket {
+        amount: "1001".to_string(),
+        ..mint.clone()
+    };
+    let response = ibc_packet_receive(
+        deps.as_mut(),
+        env.clone(),
+        mock_ibc_packet_recv("channel-0", &PortPacket::MintToken(overdrawn)).unwrap(),
+    )
+    .unwrap();
+    assert!(rejected(response));
+    // once attestors are set the counterpart no longer stands in for the route
+    STATE
+        .update(&mut deps.storage, |mut state| -> Result<_, ContractError> {
+            state.attestors = Some(AttestorSet {
+                attestors: [deps.api.addr_make("attestor")].into(),
+                threshold: 1,
+                expiry_secs: 60 * 60,
+            });
+            Ok(state)
+        })
+        .unwrap();
+    let response = ibc_packet_receive(
+        deps.as_mut(),
+        env.clone(),
+        mock_ibc_packet_recv("channel-0", &PortPacket::MintToken(mint.clone())).unwrap(),
+    )
+    .unwrap();
+    assert!(rejected(response));
+    let handled = read_state(&deps.storage, |s| s.handled_tickets.contains("ticket"));
+    assert!(!handled);
+    STATE
+        .update(&mut deps.storage, |mut state| -> Result<_, ContractError> {
+            state.attestors = None;
+            Ok(state)
+        })
+        .unwrap();
+    // nor does anything mint while the chain is deactivated
+    let set_chain_state = |storage: &mut dyn Storage, chain_state: ChainState| {
+        STATE
+            .update(storage, |mut state| -> Result<_, ContractError> {
+                state.chain_state = chain_state;
+                Ok(state)
+            })
+            .unwrap();
+    };
+    set_chain_state(&mut deps.storage, ChainState::Deactive);
+    let response = ibc_packet_receive(
+        deps.as_mut(),
+        env.clone(),
+        mock_ibc_packet_recv("channel-0", &PortPacket::MintToken(mint.clone())).unwrap(),
+    )
+    .unwrap();
+    assert!(rejected(response));
+    set_chain_state(&mut deps.storage, ChainState::Active);
+
+    let mint = PortPacket::MintToken(mint);
+    let response = ibc_packet_receive(
+        deps.as_mut(),
+        env.clone(),
+        mock_ibc_packet_recv("channel-0", &mint).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        from_json::<PacketAck>(response.acknowledgement.unwrap()).unwrap(),
+        PacketAck::Result(Binary::default())
+    );
+    assert_eq!(
+        response.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: receiver.to_string(),
+            amount: vec![cosmwasm_std::Coin::new(100u128, "uosmo")],
+        })
+    );
+
+    let packet = PortPacket::GenerateTicket(req.clone());
+    let response = ibc_packet_ack(
+        deps.as_mut(),
+        env.clone(),
+        mock_ibc_packet_ack(
+            "channel-0",
+            &packet,
+            IbcAcknowledgement::encode_json(&PacketAck::Result(Binary::default())).unwrap(),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(response.messages.is_empty());
+    assert!(GENERATE_TICKET_REQ.has(&deps.storage, req.seq));
+
+    // rejected and timed out tickets are released back to the sender
+    let refund = SubMsg::new(BankMsg::Send {
+        to_address: sender.to_string(),
+        amount: vec![cosmwasm_std::Coin::new(300u128, "uosmo")],
+    });
+    let response = ibc_packet_ack(
+        deps.as_mut(),
+        env.clone(),
+        mock_ibc_packet_ack(
+            "channel-0",
+            &packet,
+            IbcAcknowledgement::encode_json(&PacketAck::Error("rejected".to_string())).unwrap(),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(response.messages, vec![refund.clone()]);
+    assert!(!GENERATE_TICKET_REQ.has(&deps.storage, req.seq));
+    let response = ibc_packet_timeout(
+        deps.as_mut(),
+        env.clone(),
+        mock_ibc_packet_timeout("channel-0", &packet).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(response.messages, vec![refund]);
+    assert_eq!(
+        read_state(&deps.storage, |s| s.escrow_balances[TOKEN_ID]),
+        300
+    );
+
+    // the sender of an ibc hook ticket is of another chain, its recovery account is refunded
+    let recovery = deps.api.addr_make("recovery");
+    let packet = PortPacket::GenerateTicket(GenerateTicketReq {
+        sender: "cosmos1qyqszqgpqyqszqgpqyqszqgpqyqszqgpjnp7du".to_string(),
+        refund_to: Some(recovery.to_string()),
+        ..req
+    });
+    let response = ibc_packet_timeout(
+        deps.as_mut(),
+        env,
+        mock_ibc_packet_timeout("channel-0", &packet).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        response.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: recovery.to_string(),
+            amount: vec![cosmwasm_std::Coin::new(300u128, "uosmo")],
+        })]
+    );
+}
@@ -6,12 +6,43 @@ mod tests {
     use cosmwasm_std::{Addr, Coin, Empty, Uint128};
     use cw_multi_test::{App, AppBuilder, Contract, ContractWrapper, Executor};
 
+    use std::{
+        cell::RefCell,
+        collections::{BTreeMap, HashMap},
+        rc::Rc,
+    };
+
+    use cosmwasm_std::testing::MockStorage;
+    use cosmwasm_std::{
+        to_json_binary, Api, BankMsg, Binary, BlockInfo, CustomMsg, CustomQuery, Querier, Storage,
+    };
+    use cw_multi_test::{
+        error::{bail, AnyResult},
+        AppResponse, BankKeeper, CosmosRouter, FailingModule, GovFailingModule, IbcFailingModule,
+        Stargate, WasmKeeper,
+    };
+    use osmosis_std::types::osmosis::tokenfactory::v1beta1::{
+        DenomAuthorityMetadata, QueryDenomAuthorityMetadataResponse,
+    };
+    use prost::Message;
+    use serde::de::DeserializeOwned;
+
+    use crate::{
+        msg::ExecuteMsg,
+        osmosis::tokenfactory::v1beta1::{MsgBurn, QueryDenomAuthorityMetadataRequest},
+        route::{
+            Chain, ChainState, ChainType, Directive, Factor, FeeTokenFactor, TargetChainFactor,
+            Token,
+        },
+    };
+
     pub fn contract_template() -> Box<dyn Contract<Empty>> {
         let contract = ContractWrapper::new(
             crate::contract::execute,
             crate::contract::instantiate,
             crate::contract::query,
-        );
+        )
+        .with_reply(crate::reply::reply);
         Box::new(contract)
     }
 
@@ -65,6 +96,164 @@ mod tests {
         (app, cw_template_contract)
     }
 
+    const OSMO: &str = "uosmo";
+    const TOKEN_ID: &str = "osmosis-native-OSMO";
+
+    // answers tokenfactory admin queries from a shared denom -> admin table, and burns
+    // through the bank
+    #[derive(Clone, Default)]
+    struct DenomAdmins(Rc<RefCell<BTreeMap<String, String>>>);
+
+    type DenomAdminsApp = App<
+        BankKeeper,
+        MockApi,
+        MockStorage,
+        FailingModule<Empty, Empty, Empty>,
+        WasmKeeper<Empty, Empty>,
+        // staking and distribution, both failing without the `staking` feature
+        FailingModule<Empty, Empty, Empty>,
+        FailingModule<Empty, Empty, Empty>,
+        IbcFailingModule,
+        GovFailingModule,
+        DenomAdmins,
+    >;
+
+    fn instantiate_with_denom_admins() -> (DenomAdminsApp, CwTemplateContract, DenomAdmins) {
+        let denom_admins = DenomAdmins::default();
+        let mut app = AppBuilder::new()
+            .with_stargate(denom_admins.clone())
+            .build(|_, _, _| {});
+        let code_id = app.store_code(contract_template());
+        let contract = CwTemplateContract(
+            app.instantiate_contract(
+                code_id,
+                Addr::unchecked(ADMIN),
+                &InstantiateMsg {
+                    route: Addr::unchecked("wasm1rptjktp9md9u2jcjsxe4ehg3pmz5hfxquklwvt"),
+                    chain_id: "chain".to_string(),
+                },
+                &[],
+                "test",
+                None,
+            )
+            .unwrap(),
+        );
+        (app, contract, denom_admins)
+    }
+
+    impl Stargate for DenomAdmins {
+        fn execute_stargate<ExecC, QueryC>(
+            &self,
+            api: &dyn Api,
+            storage: &mut dyn Storage,
+            router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+            block: &BlockInfo,
+            _sender: Addr,
+            type_url: String,
+            value: Binary,
+        ) -> AnyResult<AppResponse>
+        where
+            ExecC: CustomMsg + DeserializeOwned + 'static,
+            QueryC: CustomQuery + DeserializeOwned + 'static,
+        {
+            if type_url != "/osmosis.tokenfactory.v1beta1.MsgBurn" {
+                bail!("unexpected stargate message: {}", type_url);
+            }
+            let msg = MsgBurn::decode(value.as_slice())?;
+            let coin = msg.amount.unwrap_or_default();
+            router.execute(
+                api,
+                storage,
+                block,
+                Addr::unchecked(msg.burn_from_address),
+                BankMsg::Burn {
+                    amount: vec![Coin::new(coin.amount.parse::<u128>()?, coin.denom)],
+                }
+                .into(),
+            )
+        }
+
+        fn query_stargate(
+            &self,
+            _api: &dyn Api,
+            _storage: &dyn Storage,
+            _querier: &dyn Querier,
+            _block: &BlockInfo,
+            path: String,
+            data: Binary,
+        ) -> AnyResult<Binary> {
+            if path != "/osmosis.tokenfactory.v1beta1.Query/DenomAuthorityMetadata" {
+                bail!("unexpected stargate query: {}", path);
+            }
+            let req = QueryDenomAuthorityMetadataRequest::decode(data.as_slice())?;
+            let admin = self.0.borrow().get(&req.denom).cloned().unwrap_or_default();
+            Ok(to_json_binary(&QueryDenomAuthorityMetadataResponse {
+                authority_metadata: Some(DenomAuthorityMetadata { admin }),
+            })?)
+        }
+    }
+
+    fn setup_escrow_token(app: &mut App, contract: &CwTemplateContract) {
+        let user = app.api().addr_make(USER);
+        app.init_modules(|router, _, storage| {
+            router
+                .bank
+                .init_balance(storage, &user, vec![Coin::new(1_000u128, OSMO)])
+        })
+        .unwrap();
+
+        let msgs = vec![
+            ExecuteMsg::SetEscrowToken {
+                token_id: TOKEN_ID.to_string(),
+                denom: Some(OSMO.to_string()),
+            },
+            ExecuteMsg::ExecDirective {
+                seq: 1,
+                directive: Directive::AddToken(Token {
+                    token_id: TOKEN_ID.to_string(),
+                    name: "OSMO".to_string(),
+                    symbol: "OSMO".to_string(),
+                    decimals: 6,
+                    icon: None,
+                    metadata: HashMap::new(),
+                }),
+            },
+            ExecuteMsg::ExecDirective {
+                seq: 2,
+                directive: Directive::AddChain(Chain {
+                    chain_id: "Bitcoin".to_string(),
+                    canister_id: "canister".to_string(),
+                    chain_type: ChainType::SettlementChain,
+                    chain_state: ChainState::Active,
+                    contract_address: None,
+                    counterparties: None,
+                    fee_token: None,
+                    receiver_validator: None,
+                }),
+            },
+            ExecuteMsg::ExecDirective {
+                seq: 3,
+                directive: Directive::UpdateFee(Factor::UpdateFeeTokenFactor(FeeTokenFactor {
+                    fee_token: OSMO.to_string(),
+                    fee_token_factor: 10,
+                })),
+            },
+            ExecuteMsg::ExecDirective {
+                seq: 4,
+                directive: Directive::UpdateFee(Factor::UpdateTargetChainFactor(
+                    TargetChainFactor {
+                        target_chain_id: "Bitcoin".to_string(),
+                        target_chain_factor: 2,
+                    },
+                )),
+            },
+        ];
+        for msg in msgs {
+            let cosmos_msg = contract.call(msg).unwrap();
+            app.execute(Addr::unchecked(ADMIN), cosmos_msg).unwrap();
+        }
+    }
+
     mod count {
         use std::collections::HashMap;
 
@@ -95,4 +284,2748 @@ mod tests {
             app.execute(Addr::unchecked(USER), cosmos_msg).unwrap();
         }
     }
+
+    mod fee {
+        use super::*;
+        use crate::{
+            msg::{
+                ExecuteMsg, GetTargetChainFeeResponse, QueryMsg, QuoteRedeemResponse,
+                RedeemCheckError,
+            },
+            route::{Directive, Factor, FeeTokenFactor},
+        };
+
+        #[test]
+        fn target_chain_fee_without_chain_factor() {
+            let (mut app, cw_template_contract) = proper_instantiate();
+
+            let msg = ExecuteMsg::ExecDirective {
+                seq: 1,
+                directive: Directive::UpdateFee(Factor::UpdateFeeTokenFactor(FeeTokenFactor {
+                    fee_token: "uosmo".to_string(),
+                    fee_token_factor: 10000,
+                })),
+            };
+            let cosmos_msg = cw_template_contract.call(msg).unwrap();
+            app.execute(Addr::unchecked(ADMIN), cosmos_msg).unwrap();
+
+            let res: GetTargetChainFeeResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    cw_template_contract.addr(),
+                    &QueryMsg::GetTargetChainFee {
+                        target_chain: "Bitcoin".to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(res.fee_token, Some("uosmo".to_string()));
+            assert_eq!(res.fee_amount, None);
+
+            let res: QuoteRedeemResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    cw_template_contract.addr(),
+                    &QueryMsg::QuoteRedeem {
+                        token_id: "Bitcoin-runes-HOPE•YOU•GET•RICH".to_string(),
+                        receiver: "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string(),
+                        target_chain: "Bitcoin".to_string(),
+                        amount: "100".to_string(),
+                        sender: app.api().addr_make(USER).to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(
+                res.errors,
+                vec![
+                    RedeemCheckError::TokenNotFound,
+                    RedeemCheckError::TargetChainNotFound,
+                    RedeemCheckError::FeeHasNotSet,
+                ]
+            );
+        }
+    }
+
+    mod simulate {
+        use super::*;
+        use crate::msg::{ExecuteMsg, QueryMsg};
+        use crate::route::{Directive, ToggleAction, ToggleState};
+        use crate::state::{GenerateTicketReq, TxAction};
+
+        #[test]
+        fn simulate_redeem_unknown_token() {
+            let (app, cw_template_contract) = proper_instantiate();
+
+            let err = app
+                .wrap()
+                .query_wasm_smart::<GenerateTicketReq>(
+                    cw_template_contract.addr(),
+                    &QueryMsg::SimulateRedeem {
+                        sender: app.api().addr_make(USER).to_string(),
+                        funds: vec![],
+                        token_id: "Bitcoin-runes-HOPE•YOU•GET•RICH".to_string(),
+                        receiver: "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string(),
+                        amount: "100".to_string(),
+                        target_chain: "Bitcoin".to_string(),
+                    },
+                )
+                .unwrap_err();
+            assert!(err.to_string().contains("TokenNotFound"));
+        }
+
+        #[test]
+        fn simulate_on_deactivated_chain() {
+            let (mut app, cw_template_contract) = proper_instantiate();
+
+            let msg = ExecuteMsg::ExecDirective {
+                seq: 1,
+                directive: Directive::ToggleChainState(ToggleState {
+                    chain_id: "chain".to_string(),
+                    action: ToggleAction::Deactivate,
+                }),
+            };
+            let cosmos_msg = cw_template_contract.call(msg).unwrap();
+            app.execute(Addr::unchecked(ADMIN), cosmos_msg).unwrap();
+
+            let err = app
+                .wrap()
+                .query_wasm_smart::<GenerateTicketReq>(
+                    cw_template_contract.addr(),
+                    &QueryMsg::SimulateGenerateTicket {
+                        signer: app.api().addr_make(USER).to_string(),
+                        funds: vec![],
+                        token_id: "Bitcoin-runes-HOPE•YOU•GET•RICH".to_string(),
+                        sender: app.api().addr_make(USER).to_string(),
+                        receiver: "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string(),
+                        amount: "100".to_string(),
+                        target_chain: "Bitcoin".to_string(),
+                        action: TxAction::Redeem,
+                        memo: None,
+                    },
+                )
+                .unwrap_err();
+            assert!(err.to_string().contains("ChainDeactive"));
+        }
+    }
+
+    mod timelock {
+        use super::*;
+        use crate::{
+            msg::{ExecuteMsg, QueryMsg},
+            state::{
+                PendingOperation, State, TimelockedAction, DEFAULT_TIMELOCK_SECS, MIN_TIMELOCK_SECS,
+            },
+        };
+
+        fn pending_operations(app: &App, contract: &CwTemplateContract) -> Vec<PendingOperation> {
+            app.wrap()
+                .query_wasm_smart(
+                    contract.addr(),
+                    &QueryMsg::GetPendingOperations {
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap()
+        }
+
+        #[test]
+        fn update_route_is_timelocked() {
+            let (mut app, contract) = proper_instantiate();
+            let admin = Addr::unchecked(ADMIN);
+            let new_route = app.api().addr_make("new_route");
+            let route = |app: &App| {
+                app.wrap()
+                    .query_wasm_smart::<State>(contract.addr(), &QueryMsg::GetState {})
+                    .unwrap()
+                    .route
+            };
+
+            for msg in [
+                ExecuteMsg::UpdateRoute {
+                    route: new_route.clone(),
+                },
+                ExecuteMsg::RefundToken {
+                    denom: NATIVE_DENOM.to_string(),
+                    receiver: app.api().addr_make(USER).to_string(),
+                    amount: "1".to_string(),
+                },
+            ] {
+                app.execute_contract(admin.clone(), contract.addr(), &msg, &[])
+                    .unwrap();
+            }
+            assert_ne!(route(&app), new_route);
+            let operations = pending_operations(&app, &contract);
+            assert_eq!(operations.len(), 2);
+            assert_eq!(
+                operations[0].action,
+                TimelockedAction::UpdateRoute {
+                    route: new_route.clone()
+                }
+            );
+            assert_eq!(
+                operations[0].executable_at,
+                operations[0].scheduled_at + DEFAULT_TIMELOCK_SECS
+            );
+
+            let err = app
+                .execute_contract(
+                    admin.clone(),
+                    contract.addr(),
+                    &ExecuteMsg::ExecuteOperation { id: 0 },
+                    &[],
+                )
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("OperationLocked"));
+            let err = app
+                .execute_contract(
+                    app.api().addr_make(USER),
+                    contract.addr(),
+                    &ExecuteMsg::CancelOperation { id: 1 },
+                    &[],
+                )
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("Unauthorized"));
+            app.execute_contract(
+                admin.clone(),
+                contract.addr(),
+                &ExecuteMsg::CancelOperation { id: 1 },
+                &[],
+            )
+            .unwrap();
+
+            app.update_block(|block| block.time = block.time.plus_seconds(DEFAULT_TIMELOCK_SECS));
+            app.execute_contract(
+                admin,
+                contract.addr(),
+                &ExecuteMsg::ExecuteOperation { id: 0 },
+                &[],
+            )
+            .unwrap();
+            assert_eq!(route(&app), new_route);
+            assert!(pending_operations(&app, &contract).is_empty());
+        }
+
+        #[test]
+        fn timelock_delay() {
+            let (mut app, contract) = proper_instantiate();
+            let admin = Addr::unchecked(ADMIN);
+
+            let err = app
+                .execute_contract(
+                    admin.clone(),
+                    contract.addr(),
+                    &ExecuteMsg::SetTimelockDelay {
+                        delay_secs: Some(0),
+                    },
+                    &[],
+                )
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("TimelockTooShort"));
+            for msg in [
+                ExecuteMsg::SetTimelockDelay {
+                    delay_secs: Some(MIN_TIMELOCK_SECS),
+                },
+                // scheduled under the current delay
+                ExecuteMsg::UpdateRoute {
+                    route: app.api().addr_make("new_route"),
+                },
+            ] {
+                app.execute_contract(admin.clone(), contract.addr(), &msg, &[])
+                    .unwrap();
+            }
+            app.update_block(|block| block.time = block.time.plus_seconds(DEFAULT_TIMELOCK_SECS));
+            app.execute_contract(
+                admin.clone(),
+                contract.addr(),
+                &ExecuteMsg::ExecuteOperation { id: 0 },
+                &[],
+            )
+            .unwrap();
+
+            app.execute_contract(
+                admin,
+                contract.addr(),
+                &ExecuteMsg::UpdateRoute {
+                    route: app.api().addr_make("other_route"),
+                },
+                &[],
+            )
+            .unwrap();
+            let operation: Option<PendingOperation> = app
+                .wrap()
+                .query_wasm_smart(contract.addr(), &QueryMsg::GetPendingOperation { id: 2 })
+                .unwrap();
+            let operation = operation.unwrap();
+            assert_eq!(
+                operation.executable_at,
+                operation.scheduled_at + MIN_TIMELOCK_SECS
+            );
+            assert_eq!(pending_operations(&app, &contract).len(), 2);
+        }
+    }
+
+    mod escrow {
+        use super::*;
+        use crate::{msg::ExecuteMsg, state::TxAction};
+
+        #[test]
+        fn lock_and_release() {
+            let (mut app, contract) = proper_instantiate();
+            setup_escrow_token(&mut app, &contract);
+            let user = app.api().addr_make(USER);
+
+            // the fee and the locked coins share the uosmo denom
+            let msg = ExecuteMsg::GenerateTicket {
+                token_id: TOKEN_ID.to_string(),
+                sender: user.to_string(),
+                receiver: "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string(),
+                amount: "300".to_string(),
+                target_chain: "Bitcoin".to_string(),
+                action: TxAction::Transfer,
+                memo: None,
+            };
+            app.execute_contract(
+                user.clone(),
+                contract.addr(),
+                &msg,
+                &[Coin::new(320u128, OSMO)],
+            )
+            .unwrap();
+            assert_eq!(
+                app.wrap()
+                    .query_balance(contract.addr(), OSMO)
+                    .unwrap()
+                    .amount,
+                Uint128::new(320)
+            );
+
+            let receiver = app.api().addr_make("receiver");
+            let msg = ExecuteMsg::PrivilegeMintToken {
+                ticket_id: "ticket".to_string(),
+                token_id: TOKEN_ID.to_string(),
+                receiver: receiver.clone(),
+                amount: "300".to_string(),
+                transmuter: None,
+                signature: None,
+                channel: None,
+                recovery: None,
+            };
+            // only the route mints, a stolen admin key can't
+            let err = app
+                .execute_contract(Addr::unchecked(ADMIN), contract.addr(), &msg, &[])
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("Unauthorized"));
+            let route = Addr::unchecked("wasm1rptjktp9md9u2jcjsxe4ehg3pmz5hfxquklwvt");
+            app.execute_contract(route.clone(), contract.addr(), &msg, &[])
+                .unwrap();
+            assert_eq!(
+                app.wrap()
+                    .query_balance(receiver.clone(), OSMO)
+                    .unwrap()
+                    .amount,
+                Uint128::new(300)
+            );
+
+            // only the locked coins can be released, not the collected fees
+            let msg = ExecuteMsg::PrivilegeMintToken {
+                ticket_id: "ticket-2".to_string(),
+                token_id: TOKEN_ID.to_string(),
+                receiver,
+                amount: "20".to_string(),
+                transmuter: None,
+                signature: None,
+                channel: None,
+                recovery: None,
+            };
+            app.execute_contract(route, contract.addr(), &msg, &[])
+                .unwrap_err();
+        }
+
+        #[test]
+        fn lock_requires_transfer_action() {
+            let (mut app, contract) = proper_instantiate();
+            setup_escrow_token(&mut app, &contract);
+            let user = app.api().addr_make(USER);
+
+            let msg = ExecuteMsg::GenerateTicket {
+                token_id: TOKEN_ID.to_string(),
+                sender: user.to_string(),
+                receiver: "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string(),
+                amount: "300".to_string(),
+                target_chain: "Bitcoin".to_string(),
+                action: TxAction::Redeem,
+                memo: None,
+            };
+            app.execute_contract(user, contract.addr(), &msg, &[Coin::new(320u128, OSMO)])
+                .unwrap_err();
+        }
+    }
+
+    mod sender {
+        use super::*;
+        use crate::{
+            msg::{ExecuteMsg, QueryMsg},
+            state::{Approval, GenerateTicketReq, TxAction},
+        };
+
+        #[test]
+        fn generate_ticket_on_behalf_of_sender() {
+            let (mut app, contract) = proper_instantiate();
+            setup_escrow_token(&mut app, &contract);
+            let user = app.api().addr_make(USER);
+            let operator = app.api().addr_make("operator");
+            app.init_modules(|router, _, storage| {
+                router
+                    .bank
+                    .init_balance(storage, &operator, vec![Coin::new(1_000u128, OSMO)])
+            })
+            .unwrap();
+
+            let msg = ExecuteMsg::GenerateTicket {
+                token_id: TOKEN_ID.to_string(),
+                sender: user.to_string(),
+                receiver: "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string(),
+                amount: "300".to_string(),
+                target_chain: "Bitcoin".to_string(),
+                action: TxAction::Transfer,
+                memo: None,
+            };
+            let funds = [Coin::new(320u128, OSMO)];
+            let err = app
+                .execute_contract(operator.clone(), contract.addr(), &msg, &funds)
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("SenderNotApproved"));
+
+            app.execute_contract(
+                user.clone(),
+                contract.addr(),
+                &ExecuteMsg::Approve {
+                    operator: operator.to_string(),
+                    expires_at: None,
+                },
+                &[],
+            )
+            .unwrap();
+            let approvals: Vec<Approval> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract.addr(),
+                    &QueryMsg::GetApprovals {
+                        sender: user.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(approvals.len(), 1);
+            assert_eq!(approvals[0].operator, operator);
+
+            let response = app
+                .execute_contract(operator.clone(), contract.addr(), &msg, &funds)
+                .unwrap();
+            // the operator's coins are locked, the ticket names it and records the granter
+            let balance = |addr: &Addr| app.wrap().query_balance(addr, OSMO).unwrap().amount;
+            assert_eq!(balance(&operator), Uint128::new(680));
+            assert_eq!(balance(&user), Uint128::new(1_000));
+            let ticket = response
+                .events
+                .iter()
+                .find(|e| e.ty == "wasm-GenerateTicketRequested")
+                .unwrap();
+            let req: GenerateTicketReq = serde_json::from_str(
+                &ticket
+                    .attributes
+                    .iter()
+                    .find(|a| a.key == "generate_ticket_request")
+                    .unwrap()
+                    .value,
+            )
+            .unwrap();
+            assert_eq!(req.sender, operator.to_string());
+            assert_eq!(req.on_behalf_of, Some(user.to_string()));
+        }
+    }
+
+    mod receiver {
+        use super::*;
+        use crate::{
+            address::{BitcoinNetwork, ReceiverValidator},
+            msg::{ExecuteMsg, QueryMsg, QuoteRedeemResponse, RedeemCheckError},
+            route::{Chain, ChainState, ChainType, Directive},
+            state::TxAction,
+        };
+
+        #[test]
+        fn receiver_validator() {
+            let (mut app, contract) = proper_instantiate();
+            setup_escrow_token(&mut app, &contract);
+            let user = app.api().addr_make(USER);
+            let update_chain = |app: &mut App, seq: u64, validator: Option<ReceiverValidator>| {
+                let msg = ExecuteMsg::ExecDirective {
+                    seq,
+                    directive: Directive::UpdateChain(Chain {
+                        chain_id: "Bitcoin".to_string(),
+                        canister_id: "canister".to_string(),
+                        chain_type: ChainType::SettlementChain,
+                        chain_state: ChainState::Active,
+                        contract_address: None,
+                        counterparties: None,
+                        fee_token: None,
+                        receiver_validator: validator,
+                    }),
+                };
+                app.execute(Addr::unchecked(ADMIN), contract.call(msg).unwrap())
+                    .unwrap();
+            };
+            update_chain(
+                &mut app,
+                5,
+                Some(ReceiverValidator::Bitcoin {
+                    network: BitcoinNetwork::Mainnet,
+                }),
+            );
+            // an update leaving the validator out keeps it
+            update_chain(&mut app, 6, None);
+
+            // a testnet address is a typo on mainnet, nothing is locked
+            let bad_receiver = "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx";
+            let quote = |app: &App, receiver: &str| -> QuoteRedeemResponse {
+                app.wrap()
+                    .query_wasm_smart(
+                        contract.addr(),
+                        &QueryMsg::QuoteRedeem {
+                            token_id: TOKEN_ID.to_string(),
+                            receiver: receiver.to_string(),
+                            target_chain: "Bitcoin".to_string(),
+                            amount: "300".to_string(),
+                            sender: user.to_string(),
+                        },
+                    )
+                    .unwrap()
+            };
+            // the quote dry-runs the redeem, so it rejects what the redeem rejects
+            let errors = quote(&app, bad_receiver).errors;
+            assert_eq!(errors.len(), 1);
+            assert!(matches!(
+                &errors[0],
+                RedeemCheckError::Other { message } if message.contains("InvalidReceiver")
+            ));
+            let quoted = quote(&app, "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq");
+            assert_eq!(quoted.errors, vec![]);
+            assert_eq!(quoted.fee_amount, Some(20));
+            let funds = [Coin::new(320u128, OSMO)];
+            for msg in [
+                ExecuteMsg::GenerateTicket {
+                    token_id: TOKEN_ID.to_string(),
+                    sender: user.to_string(),
+                    receiver: bad_receiver.to_string(),
+                    amount: "300".to_string(),
+                    target_chain: "Bitcoin".to_string(),
+                    action: TxAction::Transfer,
+                    memo: None,
+                },
+                ExecuteMsg::RedeemToken {
+                    token_id: TOKEN_ID.to_string(),
+                    receiver: bad_receiver.to_string(),
+                    amount: "300".to_string(),
+                    target_chain: "Bitcoin".to_string(),
+                },
+            ] {
+                let err = app
+                    .execute_contract(user.clone(), contract.addr(), &msg, &funds)
+                    .unwrap_err();
+                assert!(err.root_cause().to_string().contains("InvalidReceiver"));
+            }
+            assert_eq!(
+                app.wrap().query_balance(&user, OSMO).unwrap().amount,
+                Uint128::new(1_000)
+            );
+
+            app.execute_contract(
+                user,
+                contract.addr(),
+                &ExecuteMsg::RedeemToken {
+                    token_id: TOKEN_ID.to_string(),
+                    receiver: "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string(),
+                    amount: "300".to_string(),
+                    target_chain: "Bitcoin".to_string(),
+                },
+                &funds,
+            )
+            .unwrap();
+        }
+    }
+
+    mod memo {
+        use super::*;
+        use crate::{msg::ExecuteMsg, state::TxAction};
+
+        #[test]
+        fn structured_memo() {
+            let (mut app, contract) = proper_instantiate();
+            setup_escrow_token(&mut app, &contract);
+            let user = app.api().addr_make(USER);
+            let generate_ticket = |memo: &str| ExecuteMsg::GenerateTicket {
+                token_id: TOKEN_ID.to_string(),
+                sender: user.to_string(),
+                receiver: "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string(),
+                amount: "300".to_string(),
+                target_chain: "Bitcoin".to_string(),
+                action: TxAction::Transfer,
+                memo: Some(memo.to_string()),
+            };
+            let funds = [Coin::new(320u128, OSMO)];
+
+            let res = app
+                .execute_contract(
+                    user.clone(),
+                    contract.addr(),
+                    &generate_ticket(r#"{"fee_payer":"alice","referral":"bob"}"#),
+                    &funds,
+                )
+                .unwrap();
+            let event = res
+                .events
+                .iter()
+                .find(|e| e.ty == "wasm-GenerateTicketRequested")
+                .unwrap();
+            assert!(event
+                .attributes
+                .iter()
+                .any(|a| a.key == "memo_referral" && a.value == "bob"));
+
+            let err = app
+                .execute_contract(
+                    user.clone(),
+                    contract.addr(),
+                    &generate_ticket(r#"{"fee_payer":1}"#),
+                    &funds,
+                )
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("InvalidMemo"));
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract.addr(),
+                &ExecuteMsg::SetMaxMemoLength {
+                    max_memo_length: Some(8),
+                },
+                &[],
+            )
+            .unwrap();
+            let err = app
+                .execute_contract(
+                    user.clone(),
+                    contract.addr(),
+                    &generate_ticket("too long memo"),
+                    &funds,
+                )
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("MemoTooLong"));
+        }
+    }
+
+    mod counterparties {
+        use super::*;
+        use crate::{
+            msg::{ExecuteMsg, QueryMsg},
+            route::{Chain, ChainState, ChainType, Directive},
+            state::TxAction,
+        };
+
+        #[test]
+        fn counterparty_routes() {
+            let (mut app, contract) = proper_instantiate();
+            setup_escrow_token(&mut app, &contract);
+            let user = app.api().addr_make(USER);
+            let reachable_chains = |app: &App| -> Vec<String> {
+                app.wrap()
+                    .query_wasm_smart(
+                        contract.addr(),
+                        &QueryMsg::GetReachableChains {
+                            token_id: TOKEN_ID.to_string(),
+                        },
+                    )
+                    .unwrap()
+            };
+            let update_counterparties = |app: &mut App, seq: u64, counterparties: Vec<&str>| {
+                let msg = ExecuteMsg::ExecDirective {
+                    seq,
+                    directive: Directive::UpdateChain(Chain {
+                        chain_id: "Bitcoin".to_string(),
+                        canister_id: "canister".to_string(),
+                        chain_type: ChainType::SettlementChain,
+                        chain_state: ChainState::Active,
+                        contract_address: None,
+                        counterparties: Some(
+                            counterparties.into_iter().map(String::from).collect(),
+                        ),
+                        fee_token: None,
+                        receiver_validator: None,
+                    }),
+                };
+                app.execute(Addr::unchecked(ADMIN), contract.call(msg).unwrap())
+                    .unwrap();
+            };
+            assert_eq!(reachable_chains(&app), vec!["Bitcoin".to_string()]);
+
+            update_counterparties(&mut app, 5, vec!["Ethereum"]);
+            assert!(reachable_chains(&app).is_empty());
+            let msg = ExecuteMsg::GenerateTicket {
+                token_id: TOKEN_ID.to_string(),
+                sender: user.to_string(),
+                receiver: "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string(),
+                amount: "300".to_string(),
+                target_chain: "Bitcoin".to_string(),
+                action: TxAction::Transfer,
+                memo: None,
+            };
+            let funds = [Coin::new(320u128, OSMO)];
+            let err = app
+                .execute_contract(user.clone(), contract.addr(), &msg, &funds)
+                .unwrap_err();
+            assert!(err
+                .root_cause()
+                .to_string()
+                .contains("TargetChainUnreachable"));
+
+            update_counterparties(&mut app, 6, vec!["Ethereum", "chain"]);
+            assert_eq!(reachable_chains(&app), vec!["Bitcoin".to_string()]);
+            app.execute_contract(user, contract.addr(), &msg, &funds)
+                .unwrap();
+        }
+    }
+
+    mod token_routes {
+        use std::collections::BTreeSet;
+
+        use super::*;
+        use crate::{
+            msg::{ExecuteMsg, QueryMsg},
+            state::{TokenRoutes, TxAction},
+        };
+
+        #[test]
+        fn token_routes() {
+            let (mut app, contract) = proper_instantiate();
+            setup_escrow_token(&mut app, &contract);
+            let user = app.api().addr_make(USER);
+            let set_routes = |app: &mut App, routes: TokenRoutes| {
+                app.execute_contract(
+                    Addr::unchecked(ADMIN),
+                    contract.addr(),
+                    &ExecuteMsg::SetTokenRoutes {
+                        token_id: TOKEN_ID.to_string(),
+                        routes: Some(routes),
+                    },
+                    &[],
+                )
+                .unwrap();
+            };
+            let msg = ExecuteMsg::GenerateTicket {
+                token_id: TOKEN_ID.to_string(),
+                sender: user.to_string(),
+                receiver: "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string(),
+                amount: "300".to_string(),
+                target_chain: "Bitcoin".to_string(),
+                action: TxAction::Transfer,
+                memo: None,
+            };
+            let funds = [Coin::new(320u128, OSMO)];
+
+            set_routes(
+                &mut app,
+                TokenRoutes {
+                    allow: Some(BTreeSet::from(["Ethereum".to_string()])),
+                    deny: BTreeSet::new(),
+                },
+            );
+            let err = app
+                .execute_contract(user.clone(), contract.addr(), &msg, &funds)
+                .unwrap_err();
+            assert!(err
+                .root_cause()
+                .to_string()
+                .contains("TokenRouteNotAllowed"));
+            let reachable_chains: Vec<String> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract.addr(),
+                    &QueryMsg::GetReachableChains {
+                        token_id: TOKEN_ID.to_string(),
+                    },
+                )
+                .unwrap();
+            assert!(reachable_chains.is_empty());
+
+            set_routes(
+                &mut app,
+                TokenRoutes {
+                    allow: None,
+                    deny: BTreeSet::from(["Bitcoin".to_string()]),
+                },
+            );
+            app.execute_contract(user.clone(), contract.addr(), &msg, &funds)
+                .unwrap_err();
+
+            set_routes(&mut app, TokenRoutes::default());
+            app.execute_contract(user, contract.addr(), &msg, &funds)
+                .unwrap();
+        }
+    }
+
+    mod token_metadata {
+        use super::*;
+        use crate::{msg::QueryMsg, route::Token};
+
+        #[test]
+        fn get_token() {
+            let (mut app, contract) = proper_instantiate();
+            setup_escrow_token(&mut app, &contract);
+
+            let token: Option<Token> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract.addr(),
+                    &QueryMsg::GetToken {
+                        token_id: TOKEN_ID.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(token.unwrap().symbol, "OSMO");
+
+            let token: Option<Token> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract.addr(),
+                    &QueryMsg::GetToken {
+                        token_id: "unknown".to_string(),
+                    },
+                )
+                .unwrap();
+            assert!(token.is_none());
+        }
+    }
+
+    mod update_token {
+        use std::collections::HashMap;
+
+        use super::*;
+        use crate::{
+            msg::{ExecuteMsg, QueryMsg},
+            route::{Directive, Token},
+            state::{TokenMetadataChange, TxAction},
+        };
+
+        #[test]
+        fn update_token_by_admin() {
+            let (mut app, contract) = proper_instantiate();
+            setup_escrow_token(&mut app, &contract);
+            let user = app.api().addr_make(USER);
+            let update_token = |decimals: u8| ExecuteMsg::UpdateToken {
+                token_id: TOKEN_ID.to_string(),
+                name: "Osmosis".to_string(),
+                symbol: "OSMO".to_string(),
+                decimals,
+                icon: None,
+            };
+
+            let err = app
+                .execute_contract(user.clone(), contract.addr(), &update_token(6), &[])
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("Unauthorized"));
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract.addr(),
+                &update_token(6),
+                &[],
+            )
+            .unwrap();
+
+            let history: Vec<TokenMetadataChange> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract.addr(),
+                    &QueryMsg::GetTokenMetadataHistory {
+                        token_id: TOKEN_ID.to_string(),
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(history.len(), 2);
+            assert!(history[0].previous.is_none());
+            assert_eq!(history[1].previous.as_ref().unwrap().name, "OSMO");
+            assert_eq!(history[1].current.name, "Osmosis");
+            assert_eq!(history[1].updated_by, Addr::unchecked(ADMIN));
+
+            let msg = ExecuteMsg::GenerateTicket {
+                token_id: TOKEN_ID.to_string(),
+                sender: user.to_string(),
+                receiver: "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string(),
+                amount: "300".to_string(),
+                target_chain: "Bitcoin".to_string(),
+                action: TxAction::Transfer,
+                memo: None,
+            };
+            app.execute_contract(user, contract.addr(), &msg, &[Coin::new(320u128, OSMO)])
+                .unwrap();
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    contract.addr(),
+                    &update_token(8),
+                    &[],
+                )
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("DecimalsLocked"));
+
+            // directives are held to the same rule and recorded alike
+            let directive = |seq: u64, decimals: u8| ExecuteMsg::ExecDirective {
+                seq,
+                directive: Directive::UpdateToken(Token {
+                    token_id: TOKEN_ID.to_string(),
+                    name: "Osmosis".to_string(),
+                    symbol: "OSMO".to_string(),
+                    decimals,
+                    icon: Some("icon".to_string()),
+                    metadata: HashMap::new(),
+                }),
+            };
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    contract.addr(),
+                    &directive(5, 8),
+                    &[],
+                )
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("DecimalsLocked"));
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract.addr(),
+                &directive(6, 6),
+                &[],
+            )
+            .unwrap();
+            let history: Vec<TokenMetadataChange> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract.addr(),
+                    &QueryMsg::GetTokenMetadataHistory {
+                        token_id: TOKEN_ID.to_string(),
+                        start_after: Some(1),
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(history.len(), 1);
+            assert_eq!(history[0].current.icon, Some("icon".to_string()));
+        }
+    }
+
+    mod token_id {
+        use std::collections::HashMap;
+
+        use super::*;
+        use crate::{
+            msg::{ExecuteMsg, QueryMsg},
+            route::{Directive, Token},
+            state::TxAction,
+        };
+
+        #[test]
+        fn runes_token_id() {
+            let (mut app, contract) = proper_instantiate();
+            setup_escrow_token(&mut app, &contract);
+            let user = app.api().addr_make(USER);
+            let runes_id = "Bitcoin-runes-HOPE•YOU•GET•RICH";
+            let local_id = "Bitcoin-runes-HOPE.YOU.GET.RICH";
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract.addr(),
+                &ExecuteMsg::SetEscrowToken {
+                    token_id: runes_id.to_string(),
+                    denom: Some(OSMO.to_string()),
+                },
+                &[],
+            )
+            .unwrap();
+            let msg = ExecuteMsg::ExecDirective {
+                seq: 5,
+                directive: Directive::AddToken(Token {
+                    token_id: runes_id.to_string(),
+                    name: "HOPE•YOU•GET•RICH".to_string(),
+                    symbol: "RICH".to_string(),
+                    decimals: 2,
+                    icon: None,
+                    metadata: HashMap::new(),
+                }),
+            };
+            app.execute(Addr::unchecked(ADMIN), contract.call(msg).unwrap())
+                .unwrap();
+
+            for token_id in [runes_id, local_id] {
+                let token: Option<Token> = app
+                    .wrap()
+                    .query_wasm_smart(
+                        contract.addr(),
+                        &QueryMsg::GetToken {
+                            token_id: token_id.to_string(),
+                        },
+                    )
+                    .unwrap();
+                assert_eq!(token.unwrap().token_id, local_id);
+            }
+
+            let msg = ExecuteMsg::GenerateTicket {
+                token_id: local_id.to_string(),
+                sender: user.to_string(),
+                receiver: "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string(),
+                amount: "300".to_string(),
+                target_chain: "Bitcoin".to_string(),
+                action: TxAction::Transfer,
+                memo: None,
+            };
+            let res = app
+                .execute_contract(user, contract.addr(), &msg, &[Coin::new(320u128, OSMO)])
+                .unwrap();
+            let event = res
+                .events
+                .iter()
+                .find(|e| e.ty == "wasm-GenerateTicketRequested")
+                .unwrap();
+            // tickets carry the Omnity id the route knows
+            assert!(event
+                .attributes
+                .iter()
+                .any(|a| a.key == "token_id" && a.value == runes_id));
+        }
+    }
+
+    mod denom {
+        use super::*;
+        use crate::msg::QueryMsg;
+
+        #[test]
+        fn get_token_denom() {
+            let (mut app, contract) = proper_instantiate();
+            setup_escrow_token(&mut app, &contract);
+
+            let denom: Option<String> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract.addr(),
+                    &QueryMsg::GetTokenDenom {
+                        token_id: TOKEN_ID.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(denom, Some(OSMO.to_string()));
+
+            let denom: Option<String> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract.addr(),
+                    &QueryMsg::GetTokenDenom {
+                        token_id: "unknown".to_string(),
+                    },
+                )
+                .unwrap();
+            assert!(denom.is_none());
+        }
+    }
+
+    mod existing_denom {
+        use std::collections::HashMap;
+
+        use super::*;
+        use crate::{
+            msg::{ExecuteMsg, QueryMsg},
+            route::{Directive, RemoveToken, Token},
+            state::{ArchivedToken, PendingOperation, TimelockedAction},
+        };
+
+        #[test]
+        fn adopted_denom() {
+            let (mut app, contract, denom_admins) = instantiate_with_denom_admins();
+            let token_id = "Ethereum-erc20-USDT";
+            let denom = "factory/osmo1issuer/usdt";
+            let foreign_denom = "factory/osmo1issuer/usdc";
+            denom_admins
+                .0
+                .borrow_mut()
+                .insert(denom.to_string(), contract.addr().to_string());
+            denom_admins
+                .0
+                .borrow_mut()
+                .insert(foreign_denom.to_string(), "osmo1issuer".to_string());
+            let set_adopted_denom = |denom: &str| ExecuteMsg::SetAdoptedDenom {
+                token_id: token_id.to_string(),
+                denom: Some(denom.to_string()),
+            };
+
+            // the contract can't mint a denom administered by someone else
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    contract.addr(),
+                    &set_adopted_denom(foreign_denom),
+                    &[],
+                )
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("NotDenomAdmin"));
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    contract.addr(),
+                    &set_adopted_denom(OSMO),
+                    &[],
+                )
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("InvalidDenom"));
+
+            for msg in [
+                set_adopted_denom(denom),
+                ExecuteMsg::ExecDirective {
+                    seq: 1,
+                    directive: Directive::AddToken(Token {
+                        token_id: token_id.to_string(),
+                        name: "Tether USD".to_string(),
+                        symbol: "USDT".to_string(),
+                        decimals: 6,
+                        icon: None,
+                        metadata: HashMap::new(),
+                    }),
+                },
+            ] {
+                // no denom is created, the existing one is used as is
+                app.execute_contract(Addr::unchecked(ADMIN), contract.addr(), &msg, &[])
+                    .unwrap();
+            }
+
+            let token_denom: Option<String> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract.addr(),
+                    &QueryMsg::GetTokenDenom {
+                        token_id: token_id.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(token_denom, Some(denom.to_string()));
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    contract.addr(),
+                    &set_adopted_denom(denom),
+                    &[],
+                )
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("TokenAleardyExist"));
+        }
+
+        #[test]
+        fn archive_adopted_token() {
+            let (mut app, contract, denom_admins) = instantiate_with_denom_admins();
+            let route = Addr::unchecked("wasm1rptjktp9md9u2jcjsxe4ehg3pmz5hfxquklwvt");
+            let token_id = "Ethereum-erc20-USDT";
+            let denom = "factory/osmo1issuer/usdt";
+            let successor = app.api().addr_make("successor").to_string();
+            denom_admins
+                .0
+                .borrow_mut()
+                .insert(denom.to_string(), contract.addr().to_string());
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract.addr(),
+                &ExecuteMsg::SetAdoptedDenom {
+                    token_id: token_id.to_string(),
+                    denom: Some(denom.to_string()),
+                },
+                &[],
+            )
+            .unwrap();
+            app.execute_contract(
+                route.clone(),
+                contract.addr(),
+                &ExecuteMsg::ExecDirective {
+                    seq: 1,
+                    directive: Directive::AddToken(Token {
+                        token_id: token_id.to_string(),
+                        name: "Tether USD".to_string(),
+                        symbol: "USDT".to_string(),
+                        decimals: 6,
+                        icon: None,
+                        metadata: HashMap::new(),
+                    }),
+                },
+                &[],
+            )
+            .unwrap();
+
+            // archived at once, the denom admin only moves after the timelock
+            app.execute_contract(
+                route,
+                contract.addr(),
+                &ExecuteMsg::ExecDirective {
+                    seq: 2,
+                    directive: Directive::RemoveToken(RemoveToken {
+                        token_id: token_id.to_string(),
+                        sunset_secs: None,
+                        denom_admin: Some(successor.clone()),
+                        escrow_receiver: None,
+                    }),
+                },
+                &[],
+            )
+            .unwrap();
+            let archived: Option<ArchivedToken> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract.addr(),
+                    &QueryMsg::GetArchivedToken {
+                        token_id: token_id.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(archived.unwrap().denom_admin, Some(successor.clone()));
+            let operation: Option<PendingOperation> = app
+                .wrap()
+                .query_wasm_smart(contract.addr(), &QueryMsg::GetPendingOperation { id: 0 })
+                .unwrap();
+            assert_eq!(
+                operation.unwrap().action,
+                TimelockedAction::TransferDenomAdmin {
+                    token_ids: vec![token_id.to_string()],
+                    new_admin: successor,
+                }
+            );
+        }
+
+        #[test]
+        fn escrow_token_denom() {
+            let (mut app, contract) = proper_instantiate();
+            setup_escrow_token(&mut app, &contract);
+            let set_escrow_token = |token_id: &str, denom: &str| ExecuteMsg::SetEscrowToken {
+                token_id: token_id.to_string(),
+                denom: Some(denom.to_string()),
+            };
+
+            // a malformed denom is refused up front, not when coins are locked
+            for denom in ["", "1uosmo", "u osmo"] {
+                let err = app
+                    .execute_contract(
+                        Addr::unchecked(ADMIN),
+                        contract.addr(),
+                        &set_escrow_token("osmosis-native-ION", denom),
+                        &[],
+                    )
+                    .unwrap_err();
+                assert!(err.root_cause().to_string().contains("InvalidDenom"));
+            }
+            // only the admin binds, and only tokens not added yet
+            let err = app
+                .execute_contract(
+                    app.api().addr_make(USER),
+                    contract.addr(),
+                    &set_escrow_token("osmosis-native-ION", "uion"),
+                    &[],
+                )
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("Unauthorized"));
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    contract.addr(),
+                    &set_escrow_token(TOKEN_ID, "uion"),
+                    &[],
+                )
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("TokenAleardyExist"));
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract.addr(),
+                &set_escrow_token(
+                    "osmosis-native-ION",
+                    "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2",
+                ),
+                &[],
+            )
+            .unwrap();
+        }
+    }
+
+    mod remove_token {
+        use super::*;
+        use crate::{
+            msg::{ExecuteMsg, QueryMsg},
+            route::{Directive, RemoveToken, Token},
+            state::{
+                ArchivedToken, PendingOperation, TimelockedAction, TxAction, DEFAULT_TIMELOCK_SECS,
+            },
+        };
+
+        #[test]
+        fn remove_token() {
+            let (mut app, contract) = proper_instantiate();
+            setup_escrow_token(&mut app, &contract);
+            let user = app.api().addr_make(USER);
+            let route = Addr::unchecked("wasm1rptjktp9md9u2jcjsxe4ehg3pmz5hfxquklwvt");
+            let generate_ticket = ExecuteMsg::GenerateTicket {
+                token_id: TOKEN_ID.to_string(),
+                sender: user.to_string(),
+                receiver: "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string(),
+                amount: "300".to_string(),
+                target_chain: "Bitcoin".to_string(),
+                action: TxAction::Transfer,
+                memo: None,
+            };
+            let mint = |ticket_id: &str| ExecuteMsg::PrivilegeMintToken {
+                ticket_id: ticket_id.to_string(),
+                token_id: TOKEN_ID.to_string(),
+                receiver: user.clone(),
+                amount: "100".to_string(),
+                transmuter: None,
+                signature: None,
+                channel: None,
+                recovery: None,
+            };
+            let archive_token = ExecuteMsg::ArchiveToken {
+                token_id: TOKEN_ID.to_string(),
+            };
+            let remove_token = |denom_admin: Option<&str>| ExecuteMsg::ExecDirective {
+                seq: 5,
+                directive: Directive::RemoveToken(RemoveToken {
+                    token_id: TOKEN_ID.to_string(),
+                    sunset_secs: Some(100),
+                    denom_admin: denom_admin.map(str::to_string),
+                    escrow_receiver: None,
+                }),
+            };
+
+            let err = app
+                .execute_contract(
+                    route.clone(),
+                    contract.addr(),
+                    &remove_token(Some("not an address")),
+                    &[],
+                )
+                .unwrap_err();
+            assert!(err
+                .root_cause()
+                .to_string()
+                .contains("Error decoding bech32"));
+            app.execute_contract(route.clone(), contract.addr(), &remove_token(None), &[])
+                .unwrap();
+
+            // redeems stay open during the sunset, and so do releases of the escrowed coins
+            app.execute_contract(
+                user.clone(),
+                contract.addr(),
+                &generate_ticket,
+                &[Coin::new(320u128, OSMO)],
+            )
+            .unwrap();
+            app.execute_contract(route.clone(), contract.addr(), &mint("ticket"), &[])
+                .unwrap();
+            assert_eq!(
+                app.wrap().query_balance(&user, OSMO).unwrap().amount,
+                Uint128::new(1_000 - 320 + 100)
+            );
+            let err = app
+                .execute_contract(user.clone(), contract.addr(), &archive_token, &[])
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("SunsetNotOver"));
+
+            app.update_block(|block| block.time = block.time.plus_seconds(100));
+            let err = app
+                .execute_contract(
+                    user.clone(),
+                    contract.addr(),
+                    &generate_ticket,
+                    &[Coin::new(320u128, OSMO)],
+                )
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("TokenRemoved"));
+            let err = app
+                .execute_contract(route, contract.addr(), &mint("late ticket"), &[])
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("TokenRemoved"));
+            let escrowed = app
+                .wrap()
+                .query_balance(contract.addr(), OSMO)
+                .unwrap()
+                .amount;
+            app.execute_contract(user, contract.addr(), &archive_token, &[])
+                .unwrap();
+
+            let token: Option<Token> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract.addr(),
+                    &QueryMsg::GetToken {
+                        token_id: TOKEN_ID.to_string(),
+                    },
+                )
+                .unwrap();
+            assert!(token.is_none());
+            let archived: Option<ArchivedToken> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract.addr(),
+                    &QueryMsg::GetArchivedToken {
+                        token_id: TOKEN_ID.to_string(),
+                    },
+                )
+                .unwrap();
+            let archived = archived.unwrap();
+            assert_eq!(archived.denom, OSMO);
+            assert_eq!(archived.escrow_balance, 200);
+            // the native denom of an escrowed token has no admin to hand over
+            assert_eq!(archived.denom_admin, None);
+
+            // with no receiver named, the escrowed coins stay in the contract
+            let operations: Vec<PendingOperation> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract.addr(),
+                    &QueryMsg::GetPendingOperations {
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            assert!(operations.is_empty());
+            assert_eq!(
+                app.wrap()
+                    .query_balance(contract.addr(), OSMO)
+                    .unwrap()
+                    .amount,
+                escrowed
+            );
+
+            // refunds only reach the collected fee, never the archived escrow
+            let admin = Addr::unchecked(ADMIN);
+            for amount in ["21", "20"] {
+                app.execute_contract(
+                    admin.clone(),
+                    contract.addr(),
+                    &ExecuteMsg::RefundToken {
+                        denom: OSMO.to_string(),
+                        receiver: admin.to_string(),
+                        amount: amount.to_string(),
+                    },
+                    &[],
+                )
+                .unwrap();
+            }
+            app.update_block(|block| block.time = block.time.plus_seconds(DEFAULT_TIMELOCK_SECS));
+            let err = app
+                .execute_contract(
+                    admin.clone(),
+                    contract.addr(),
+                    &ExecuteMsg::ExecuteOperation { id: 0 },
+                    &[],
+                )
+                .unwrap_err();
+            assert!(err
+                .root_cause()
+                .to_string()
+                .contains("InsufficientRefundable"));
+            app.execute_contract(
+                admin,
+                contract.addr(),
+                &ExecuteMsg::ExecuteOperation { id: 1 },
+                &[],
+            )
+            .unwrap();
+            assert_eq!(
+                app.wrap()
+                    .query_balance(contract.addr(), OSMO)
+                    .unwrap()
+                    .amount,
+                Uint128::new(200)
+            );
+        }
+
+        #[test]
+        fn release_archived_escrow() {
+            let (mut app, contract) = proper_instantiate();
+            setup_escrow_token(&mut app, &contract);
+            let user = app.api().addr_make(USER);
+            let receiver = app.api().addr_make("receiver");
+            app.execute_contract(
+                user,
+                contract.addr(),
+                &ExecuteMsg::GenerateTicket {
+                    token_id: TOKEN_ID.to_string(),
+                    sender: app.api().addr_make(USER).to_string(),
+                    receiver: "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string(),
+                    amount: "300".to_string(),
+                    target_chain: "Bitcoin".to_string(),
+                    action: TxAction::Transfer,
+                    memo: None,
+                },
+                &[Coin::new(320u128, OSMO)],
+            )
+            .unwrap();
+
+            // archived at once, the escrow only goes to the named receiver after the timelock
+            app.execute_contract(
+                Addr::unchecked("wasm1rptjktp9md9u2jcjsxe4ehg3pmz5hfxquklwvt"),
+                contract.addr(),
+                &ExecuteMsg::ExecDirective {
+                    seq: 5,
+                    directive: Directive::RemoveToken(RemoveToken {
+                        token_id: TOKEN_ID.to_string(),
+                        sunset_secs: None,
+                        denom_admin: None,
+                        escrow_receiver: Some(receiver.to_string()),
+                    }),
+                },
+                &[],
+            )
+            .unwrap();
+            let operations: Vec<PendingOperation> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract.addr(),
+                    &QueryMsg::GetPendingOperations {
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(
+                operations[0].action,
+                TimelockedAction::ReleaseArchivedEscrow {
+                    token_id: TOKEN_ID.to_string(),
+                    receiver: receiver.to_string(),
+                }
+            );
+            assert!(app
+                .wrap()
+                .query_balance(&receiver, OSMO)
+                .unwrap()
+                .amount
+                .is_zero());
+
+            app.update_block(|block| block.time = block.time.plus_seconds(DEFAULT_TIMELOCK_SECS));
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract.addr(),
+                &ExecuteMsg::ExecuteOperation {
+                    id: operations[0].id,
+                },
+                &[],
+            )
+            .unwrap();
+            assert_eq!(
+                app.wrap().query_balance(&receiver, OSMO).unwrap().amount,
+                Uint128::new(300)
+            );
+        }
+    }
+
+    mod denom_admin {
+        use super::*;
+        use crate::{msg::ExecuteMsg, state::DEFAULT_TIMELOCK_SECS};
+
+        #[test]
+        fn timelocked_denom_admin_transfer() {
+            let (mut app, contract) = proper_instantiate();
+            setup_escrow_token(&mut app, &contract);
+            let admin = Addr::unchecked(ADMIN);
+            let successor = app.api().addr_make("successor").to_string();
+
+            // the escrowed token has no denom admin to hand over
+            let err = app
+                .execute_contract(
+                    admin.clone(),
+                    contract.addr(),
+                    &ExecuteMsg::TransferDenomAdmin {
+                        token_id: TOKEN_ID.to_string(),
+                        new_admin: successor.clone(),
+                    },
+                    &[],
+                )
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("TokenNotMinted"));
+
+            let transfer_all = ExecuteMsg::TransferAllDenomAdmins {
+                new_admin: successor,
+            };
+            let err = app
+                .execute_contract(
+                    app.api().addr_make(USER),
+                    contract.addr(),
+                    &transfer_all,
+                    &[],
+                )
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("Unauthorized"));
+            for _ in 0..2 {
+                app.execute_contract(admin.clone(), contract.addr(), &transfer_all, &[])
+                    .unwrap();
+            }
+
+            let err = app
+                .execute_contract(
+                    admin.clone(),
+                    contract.addr(),
+                    &ExecuteMsg::ExecuteOperation { id: 0 },
+                    &[],
+                )
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("OperationLocked"));
+            app.execute_contract(
+                admin.clone(),
+                contract.addr(),
+                &ExecuteMsg::CancelOperation { id: 1 },
+                &[],
+            )
+            .unwrap();
+
+            app.update_block(|block| block.time = block.time.plus_seconds(DEFAULT_TIMELOCK_SECS));
+            app.execute_contract(
+                admin.clone(),
+                contract.addr(),
+                &ExecuteMsg::ExecuteOperation { id: 0 },
+                &[],
+            )
+            .unwrap();
+            for id in [0, 1] {
+                let err = app
+                    .execute_contract(
+                        admin.clone(),
+                        contract.addr(),
+                        &ExecuteMsg::ExecuteOperation { id },
+                        &[],
+                    )
+                    .unwrap_err();
+                assert!(err.root_cause().to_string().contains("OperationNotFound"));
+            }
+        }
+    }
+
+    mod attestors {
+        use std::collections::BTreeSet;
+
+        use super::*;
+        use crate::{
+            msg::{ExecuteMsg, QueryMsg},
+            state::{AttestorSet, MintAttestation, TxAction, DEFAULT_TIMELOCK_SECS},
+        };
+
+        #[test]
+        fn threshold_mint() {
+            let (mut app, contract) = proper_instantiate();
+            setup_escrow_token(&mut app, &contract);
+            let user = app.api().addr_make(USER);
+            let admin = Addr::unchecked(ADMIN);
+            let route = Addr::unchecked("wasm1rptjktp9md9u2jcjsxe4ehg3pmz5hfxquklwvt");
+            let attestors: Vec<Addr> = ["attestor-1", "attestor-2", "attestor-3"]
+                .iter()
+                .map(|name| app.api().addr_make(name))
+                .collect();
+
+            // lock coins to release
+            app.execute_contract(
+                user.clone(),
+                contract.addr(),
+                &ExecuteMsg::GenerateTicket {
+                    token_id: TOKEN_ID.to_string(),
+                    sender: user.to_string(),
+                    receiver: "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string(),
+                    amount: "300".to_string(),
+                    target_chain: "Bitcoin".to_string(),
+                    action: TxAction::Transfer,
+                    memo: None,
+                },
+                &[Coin::new(320u128, OSMO)],
+            )
+            .unwrap();
+
+            let err = app
+                .execute_contract(
+                    admin.clone(),
+                    contract.addr(),
+                    &ExecuteMsg::SetAttestors {
+                        attestors: Some(AttestorSet {
+                            attestors: attestors.iter().cloned().collect(),
+                            threshold: 4,
+                            expiry_secs: 600,
+                        }),
+                    },
+                    &[],
+                )
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("InvalidAttestors"));
+            for msg in [
+                ExecuteMsg::SetAttestors {
+                    attestors: Some(AttestorSet {
+                        attestors: attestors.iter().cloned().collect(),
+                        threshold: 2,
+                        expiry_secs: 600,
+                    }),
+                },
+                ExecuteMsg::ExecuteOperation { id: 0 },
+            ] {
+                app.update_block(|block| {
+                    block.time = block.time.plus_seconds(DEFAULT_TIMELOCK_SECS)
+                });
+                app.execute_contract(admin.clone(), contract.addr(), &msg, &[])
+                    .unwrap();
+            }
+
+            let receiver = app.api().addr_make("receiver");
+            let mint = |ticket_id: &str, amount: &str| ExecuteMsg::PrivilegeMintToken {
+                ticket_id: ticket_id.to_string(),
+                token_id: TOKEN_ID.to_string(),
+                receiver: receiver.clone(),
+                amount: amount.to_string(),
+                transmuter: None,
+                signature: None,
+                channel: None,
+                recovery: None,
+            };
+            let balance = |app: &App| {
+                app.wrap()
+                    .query_balance(receiver.clone(), OSMO)
+                    .unwrap()
+                    .amount
+            };
+            let attestations = |app: &App, ticket_id: &str| -> Vec<MintAttestation> {
+                app.wrap()
+                    .query_wasm_smart(
+                        contract.addr(),
+                        &QueryMsg::GetMintAttestations {
+                            ticket_id: ticket_id.to_string(),
+                        },
+                    )
+                    .unwrap()
+            };
+
+            // neither the route nor the admin can mint alone any more
+            for sender in [route, admin] {
+                let err = app
+                    .execute_contract(sender, contract.addr(), &mint("ticket", "100"), &[])
+                    .unwrap_err();
+                assert!(err.root_cause().to_string().contains("Unauthorized"));
+            }
+
+            // attestors disagreeing on the amount attest different mints
+            app.execute_contract(
+                attestors[0].clone(),
+                contract.addr(),
+                &mint("ticket", "100"),
+                &[],
+            )
+            .unwrap();
+            app.execute_contract(
+                attestors[1].clone(),
+                contract.addr(),
+                &mint("ticket", "200"),
+                &[],
+            )
+            .unwrap();
+            assert_eq!(attestations(&app, "ticket").len(), 2);
+            assert_eq!(balance(&app), Uint128::zero());
+
+            app.execute_contract(
+                attestors[2].clone(),
+                contract.addr(),
+                &mint("ticket", "100"),
+                &[],
+            )
+            .unwrap();
+            assert_eq!(balance(&app), Uint128::new(100));
+            assert!(attestations(&app, "ticket").is_empty());
+
+            // an expired attestation starts over
+            app.execute_contract(
+                attestors[0].clone(),
+                contract.addr(),
+                &mint("ticket-2", "100"),
+                &[],
+            )
+            .unwrap();
+            app.update_block(|block| block.time = block.time.plus_seconds(600));
+            app.execute_contract(
+                attestors[1].clone(),
+                contract.addr(),
+                &mint("ticket-2", "100"),
+                &[],
+            )
+            .unwrap();
+            let pending = attestations(&app, "ticket-2");
+            assert_eq!(pending.len(), 1);
+            assert_eq!(pending[0].attestors.len(), 1);
+            assert_eq!(balance(&app), Uint128::new(100));
+        }
+
+        #[test]
+        fn rotated_out_attestor() {
+            let (mut app, contract) = proper_instantiate();
+            setup_escrow_token(&mut app, &contract);
+            let user = app.api().addr_make(USER);
+            let admin = Addr::unchecked(ADMIN);
+            let attestors: Vec<Addr> = ["attestor-1", "attestor-2", "attestor-3"]
+                .iter()
+                .map(|name| app.api().addr_make(name))
+                .collect();
+
+            app.execute_contract(
+                user.clone(),
+                contract.addr(),
+                &ExecuteMsg::GenerateTicket {
+                    token_id: TOKEN_ID.to_string(),
+                    sender: user.to_string(),
+                    receiver: "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string(),
+                    amount: "300".to_string(),
+                    target_chain: "Bitcoin".to_string(),
+                    action: TxAction::Transfer,
+                    memo: None,
+                },
+                &[Coin::new(320u128, OSMO)],
+            )
+            .unwrap();
+
+            // attestations outlive the timelock of the rotation
+            let set_attestors = |app: &mut App, id: u64, attestors: &[Addr]| {
+                app.execute_contract(
+                    admin.clone(),
+                    contract.addr(),
+                    &ExecuteMsg::SetAttestors {
+                        attestors: Some(AttestorSet {
+                            attestors: attestors.iter().cloned().collect(),
+                            threshold: 2,
+                            expiry_secs: 2 * DEFAULT_TIMELOCK_SECS,
+                        }),
+                    },
+                    &[],
+                )
+                .unwrap();
+                app.update_block(|block| {
+                    block.time = block.time.plus_seconds(DEFAULT_TIMELOCK_SECS)
+                });
+                app.execute_contract(
+                    admin.clone(),
+                    contract.addr(),
+                    &ExecuteMsg::ExecuteOperation { id },
+                    &[],
+                )
+                .unwrap();
+            };
+            set_attestors(&mut app, 0, &attestors[..2]);
+
+            let receiver = app.api().addr_make("receiver");
+            let mint = ExecuteMsg::PrivilegeMintToken {
+                ticket_id: "ticket".to_string(),
+                token_id: TOKEN_ID.to_string(),
+                receiver: receiver.clone(),
+                amount: "100".to_string(),
+                transmuter: None,
+                signature: None,
+                channel: None,
+                recovery: None,
+            };
+            let balance = |app: &App| {
+                app.wrap()
+                    .query_balance(receiver.clone(), OSMO)
+                    .unwrap()
+                    .amount
+            };
+            app.execute_contract(attestors[0].clone(), contract.addr(), &mint, &[])
+                .unwrap();
+
+            // the vote of a rotated out attestor no longer counts
+            set_attestors(&mut app, 1, &attestors[1..]);
+            app.execute_contract(attestors[1].clone(), contract.addr(), &mint, &[])
+                .unwrap();
+            assert_eq!(balance(&app), Uint128::zero());
+            let pending: Vec<MintAttestation> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract.addr(),
+                    &QueryMsg::GetMintAttestations {
+                        ticket_id: "ticket".to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(pending[0].attestors, BTreeSet::from([attestors[1].clone()]));
+
+            app.execute_contract(attestors[2].clone(), contract.addr(), &mint, &[])
+                .unwrap();
+            assert_eq!(balance(&app), Uint128::new(100));
+        }
+    }
+
+    mod signed_mint {
+        use super::*;
+        use crate::{
+            msg::ExecuteMsg,
+            route::Directive,
+            state::{TxAction, DEFAULT_TIMELOCK_SECS},
+        };
+
+        #[test]
+        fn hub_signed_mint() {
+            use crate::hub_key::{mint_ticket_message, HubKey, SignatureScheme};
+            use crate::types::MintTicket;
+            use cosmwasm_std::Binary;
+            use ed25519_zebra::{SigningKey, VerificationKey};
+
+            let (mut app, contract) = proper_instantiate();
+            setup_escrow_token(&mut app, &contract);
+            let user = app.api().addr_make(USER);
+            let relayer = app.api().addr_make("relayer");
+            let receiver = app.api().addr_make("receiver");
+            let route = Addr::unchecked("wasm1rptjktp9md9u2jcjsxe4ehg3pmz5hfxquklwvt");
+            let signing_key = SigningKey::from([7u8; 32]);
+
+            app.execute_contract(
+                user.clone(),
+                contract.addr(),
+                &ExecuteMsg::GenerateTicket {
+                    token_id: TOKEN_ID.to_string(),
+                    sender: user.to_string(),
+                    receiver: "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string(),
+                    amount: "300".to_string(),
+                    target_chain: "Bitcoin".to_string(),
+                    action: TxAction::Transfer,
+                    memo: None,
+                },
+                &[Coin::new(320u128, OSMO)],
+            )
+            .unwrap();
+            app.execute_contract(
+                route.clone(),
+                contract.addr(),
+                &ExecuteMsg::ExecDirective {
+                    seq: 5,
+                    directive: Directive::UpdateHubKey(Some(HubKey {
+                        scheme: SignatureScheme::Ed25519,
+                        public_key: Binary::from(VerificationKey::from(&signing_key).as_ref()),
+                    })),
+                },
+                &[],
+            )
+            .unwrap();
+            app.update_block(|block| block.time = block.time.plus_seconds(DEFAULT_TIMELOCK_SECS));
+            app.execute_contract(
+                route,
+                contract.addr(),
+                &ExecuteMsg::ExecuteOperation { id: 0 },
+                &[],
+            )
+            .unwrap();
+
+            let message = mint_ticket_message(
+                &contract.addr(),
+                "chain",
+                &MintTicket {
+                    ticket_id: "ticket".to_string(),
+                    token_id: TOKEN_ID.to_string(),
+                    receiver: receiver.clone(),
+                    amount: "100".to_string(),
+                    transmuter: None,
+                    channel: None,
+                    recovery: None,
+                },
+            );
+            let mint = |amount: &str| ExecuteMsg::PrivilegeMintToken {
+                ticket_id: "ticket".to_string(),
+                token_id: TOKEN_ID.to_string(),
+                receiver: receiver.clone(),
+                amount: amount.to_string(),
+                transmuter: None,
+                signature: Some(Binary::from(signing_key.sign(&message).to_bytes())),
+                channel: None,
+                recovery: None,
+            };
+
+            // the signature covers the amount
+            let err = app
+                .execute_contract(relayer.clone(), contract.addr(), &mint("300"), &[])
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("InvalidSignature"));
+            app.execute_contract(relayer.clone(), contract.addr(), &mint("100"), &[])
+                .unwrap();
+            assert_eq!(
+                app.wrap()
+                    .query_balance(receiver.clone(), OSMO)
+                    .unwrap()
+                    .amount,
+                Uint128::new(100)
+            );
+            let err = app
+                .execute_contract(relayer, contract.addr(), &mint("100"), &[])
+                .unwrap_err();
+            assert!(err
+                .root_cause()
+                .to_string()
+                .contains("TicketAlreadyHandled"));
+        }
+    }
+
+    mod signed_directive {
+        use super::*;
+        use crate::{
+            msg::{ExecuteMsg, QueryMsg},
+            route::{Directive, Factor, TargetChainFactor, Token},
+            state::DEFAULT_TIMELOCK_SECS,
+        };
+
+        #[test]
+        fn hub_signed_directive() {
+            use crate::hub_key::{directive_message, HubKey, SignatureScheme};
+            use cosmwasm_std::{to_json_vec, Binary};
+            use ed25519_zebra::{SigningKey, VerificationKey};
+
+            let (mut app, contract) = proper_instantiate();
+            setup_escrow_token(&mut app, &contract);
+            let relayer = app.api().addr_make("relayer");
+            let route = Addr::unchecked("wasm1rptjktp9md9u2jcjsxe4ehg3pmz5hfxquklwvt");
+            let hub_key = |signing_key: &SigningKey| HubKey {
+                scheme: SignatureScheme::Ed25519,
+                public_key: Binary::from(VerificationKey::from(signing_key).as_ref()),
+            };
+            let signing_key = SigningKey::from([7u8; 32]);
+            let signed_raw = |signing_key: &SigningKey, seq: u64, directive: Vec<u8>| {
+                let message = directive_message(&contract.addr(), "chain", seq, &directive);
+                ExecuteMsg::ExecSignedDirective {
+                    seq,
+                    directive: Binary::from(directive),
+                    signature: Binary::from(signing_key.sign(&message).to_bytes()),
+                }
+            };
+            let signed = |signing_key: &SigningKey, seq: u64, directive: Directive| {
+                signed_raw(signing_key, seq, to_json_vec(&directive).unwrap())
+            };
+            let update_fee =
+                Directive::UpdateFee(Factor::UpdateTargetChainFactor(TargetChainFactor {
+                    target_chain_id: "Bitcoin".to_string(),
+                    target_chain_factor: 3,
+                }));
+
+            app.execute_contract(
+                route.clone(),
+                contract.addr(),
+                &ExecuteMsg::ExecDirective {
+                    seq: 5,
+                    directive: Directive::UpdateHubKey(Some(hub_key(&signing_key))),
+                },
+                &[],
+            )
+            .unwrap();
+            // the key only counts once the timelock has passed
+            let err = app
+                .execute_contract(
+                    relayer.clone(),
+                    contract.addr(),
+                    &signed(&signing_key, 6, update_fee.clone()),
+                    &[],
+                )
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("no hub key"));
+            app.update_block(|block| block.time = block.time.plus_seconds(DEFAULT_TIMELOCK_SECS));
+            app.execute_contract(
+                route.clone(),
+                contract.addr(),
+                &ExecuteMsg::ExecuteOperation { id: 0 },
+                &[],
+            )
+            .unwrap();
+
+            // once the hub key is active, unsigned directives fail even from the route
+            for (sender, error) in [
+                (relayer.clone(), "Unauthorized"),
+                (route, "InvalidSignature"),
+                (Addr::unchecked(ADMIN), "InvalidSignature"),
+            ] {
+                let err = app
+                    .execute_contract(
+                        sender,
+                        contract.addr(),
+                        &ExecuteMsg::ExecDirective {
+                            seq: 6,
+                            directive: update_fee.clone(),
+                        },
+                        &[],
+                    )
+                    .unwrap_err();
+                assert!(err.root_cause().to_string().contains(error));
+            }
+
+            // the signature covers the sequence
+            let mut msg = signed(&signing_key, 6, update_fee.clone());
+            if let ExecuteMsg::ExecSignedDirective { seq, .. } = &mut msg {
+                *seq = 7;
+            }
+            let err = app
+                .execute_contract(relayer.clone(), contract.addr(), &msg, &[])
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("InvalidSignature"));
+
+            let msg = signed(&signing_key, 6, update_fee);
+            app.execute_contract(relayer.clone(), contract.addr(), &msg, &[])
+                .unwrap();
+            let err = app
+                .execute_contract(relayer.clone(), contract.addr(), &msg, &[])
+                .unwrap_err();
+            assert!(err
+                .root_cause()
+                .to_string()
+                .contains("DirectiveAlreadyHandled"));
+
+            // the bytes the hub encoded are verified, whatever order it gave the metadata
+            let update_token = format!(
+                r#"{{"update_token":{{"token_id":"{}","name":"OSMO","symbol":"OSMO","decimals":6,"icon":null,"metadata":{{"z":"1","a":"2"}}}}}}"#,
+                TOKEN_ID
+            );
+            app.execute_contract(
+                relayer.clone(),
+                contract.addr(),
+                &signed_raw(&signing_key, 7, update_token.into_bytes()),
+                &[],
+            )
+            .unwrap();
+            let token: Option<Token> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract.addr(),
+                    &QueryMsg::GetToken {
+                        token_id: TOKEN_ID.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(token.unwrap().metadata.len(), 2);
+
+            // the current key signs its successor
+            let next_key = SigningKey::from([8u8; 32]);
+            for msg in [
+                signed(
+                    &signing_key,
+                    8,
+                    Directive::UpdateHubKey(Some(hub_key(&next_key))),
+                ),
+                ExecuteMsg::ExecuteOperation { id: 1 },
+                signed(&next_key, 9, Directive::UpdateHubKey(None)),
+            ] {
+                app.update_block(|block| {
+                    block.time = block.time.plus_seconds(DEFAULT_TIMELOCK_SECS)
+                });
+                app.execute_contract(relayer.clone(), contract.addr(), &msg, &[])
+                    .unwrap();
+            }
+        }
+    }
+
+    mod relay_reward {
+        use super::*;
+        use crate::{
+            msg::{ExecuteMsg, QueryMsg},
+            route::Directive,
+            state::{TxAction, DEFAULT_TIMELOCK_SECS},
+        };
+
+        #[test]
+        fn relay_reward() {
+            use crate::hub_key::{mint_ticket_message, HubKey, SignatureScheme};
+            use crate::state::RelayerStats;
+            use crate::types::MintTicket;
+            use cosmwasm_std::Binary;
+            use ed25519_zebra::{SigningKey, VerificationKey};
+
+            let (mut app, contract) = proper_instantiate();
+            setup_escrow_token(&mut app, &contract);
+            let admin = Addr::unchecked(ADMIN);
+            let user = app.api().addr_make(USER);
+            let relayer = app.api().addr_make("relayer");
+            let receiver = app.api().addr_make("receiver");
+            let route = Addr::unchecked("wasm1rptjktp9md9u2jcjsxe4ehg3pmz5hfxquklwvt");
+            let signing_key = SigningKey::from([7u8; 32]);
+
+            // locks 300 uosmo and collects a fee of 20 uosmo
+            let generate_ticket = |app: &mut App| {
+                app.execute_contract(
+                    user.clone(),
+                    contract.addr(),
+                    &ExecuteMsg::GenerateTicket {
+                        token_id: TOKEN_ID.to_string(),
+                        sender: user.to_string(),
+                        receiver: "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string(),
+                        amount: "300".to_string(),
+                        target_chain: "Bitcoin".to_string(),
+                        action: TxAction::Transfer,
+                        memo: None,
+                    },
+                    &[Coin::new(320u128, OSMO)],
+                )
+                .unwrap();
+            };
+            generate_ticket(&mut app);
+            app.execute_contract(
+                route.clone(),
+                contract.addr(),
+                &ExecuteMsg::ExecDirective {
+                    seq: 5,
+                    directive: Directive::UpdateHubKey(Some(HubKey {
+                        scheme: SignatureScheme::Ed25519,
+                        public_key: Binary::from(VerificationKey::from(&signing_key).as_ref()),
+                    })),
+                },
+                &[],
+            )
+            .unwrap();
+            app.execute_contract(
+                admin.clone(),
+                contract.addr(),
+                &ExecuteMsg::SetRelayReward {
+                    reward: Some(Uint128::new(15)),
+                },
+                &[],
+            )
+            .unwrap();
+            app.update_block(|block| block.time = block.time.plus_seconds(DEFAULT_TIMELOCK_SECS));
+            for id in [0, 1] {
+                app.execute_contract(
+                    admin.clone(),
+                    contract.addr(),
+                    &ExecuteMsg::ExecuteOperation { id },
+                    &[],
+                )
+                .unwrap();
+            }
+
+            let mint = |ticket_id: &str| {
+                let message = mint_ticket_message(
+                    &contract.addr(),
+                    "chain",
+                    &MintTicket {
+                        ticket_id: ticket_id.to_string(),
+                        token_id: TOKEN_ID.to_string(),
+                        receiver: receiver.clone(),
+                        amount: "100".to_string(),
+                        transmuter: None,
+                        channel: None,
+                        recovery: None,
+                    },
+                );
+                ExecuteMsg::PrivilegeMintToken {
+                    ticket_id: ticket_id.to_string(),
+                    token_id: TOKEN_ID.to_string(),
+                    receiver: receiver.clone(),
+                    amount: "100".to_string(),
+                    transmuter: None,
+                    signature: Some(Binary::from(signing_key.sign(&message).to_bytes())),
+                    channel: None,
+                    recovery: None,
+                }
+            };
+            let balance = |app: &App| {
+                app.wrap()
+                    .query_balance(relayer.clone(), OSMO)
+                    .unwrap()
+                    .amount
+            };
+            let stats = |app: &App| {
+                app.wrap()
+                    .query_wasm_smart::<Option<RelayerStats>>(
+                        contract.addr(),
+                        &QueryMsg::GetRelayer {
+                            relayer: relayer.to_string(),
+                        },
+                    )
+                    .unwrap()
+                    .unwrap()
+            };
+
+            // unsigned tickets minted by the route earn nothing, leaving 20 collected fees
+            let unsigned = ExecuteMsg::PrivilegeMintToken {
+                ticket_id: "ticket-1".to_string(),
+                token_id: TOKEN_ID.to_string(),
+                receiver: receiver.clone(),
+                amount: "100".to_string(),
+                transmuter: None,
+                signature: None,
+                channel: None,
+                recovery: None,
+            };
+            app.execute_contract(route.clone(), contract.addr(), &unsigned, &[])
+                .unwrap();
+            app.execute_contract(relayer.clone(), contract.addr(), &mint("ticket-2"), &[])
+                .unwrap();
+            assert_eq!(balance(&app), Uint128::new(15));
+
+            // only 5 collected fees are left, escrowed coins are never paid out
+            app.execute_contract(relayer.clone(), contract.addr(), &mint("ticket-3"), &[])
+                .unwrap();
+            assert_eq!(balance(&app), Uint128::new(20));
+            assert_eq!(
+                stats(&app),
+                RelayerStats {
+                    relayer: relayer.clone(),
+                    tickets: 2,
+                    earned: Uint128::new(30),
+                    paid: Uint128::new(20),
+                }
+            );
+            let err = app
+                .execute_contract(
+                    relayer.clone(),
+                    contract.addr(),
+                    &ExecuteMsg::ClaimRelayRewards {},
+                    &[],
+                )
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("NoRelayRewards"));
+
+            generate_ticket(&mut app);
+            app.execute_contract(
+                relayer.clone(),
+                contract.addr(),
+                &ExecuteMsg::ClaimRelayRewards {},
+                &[],
+            )
+            .unwrap();
+            assert_eq!(balance(&app), Uint128::new(30));
+            assert_eq!(stats(&app).paid, Uint128::new(30));
+
+            // nor are the coins of failed forwards, owed to their beneficiaries
+            let forward = ExecuteMsg::PrivilegeMintToken {
+                ticket_id: "ticket-forward".to_string(),
+                token_id: TOKEN_ID.to_string(),
+                receiver: Addr::unchecked("cosmos1qypqxpqpqgpsgqgzqvzqzqsrqsqsyqcyy2wt4y"),
+                amount: "100".to_string(),
+                transmuter: None,
+                signature: None,
+                channel: Some("channel-0".to_string()),
+                recovery: None,
+            };
+            let res = app
+                .execute_contract(route, contract.addr(), &forward, &[])
+                .unwrap();
+            assert!(res.events.iter().any(|e| e.ty == "wasm-ForwardFailed"));
+            let other = app.api().addr_make("other_relayer");
+            app.execute_contract(other.clone(), contract.addr(), &mint("ticket-4"), &[])
+                .unwrap();
+            // 10 collected fees are left of the 15 earned
+            assert_eq!(
+                app.wrap().query_balance(&other, OSMO).unwrap().amount,
+                Uint128::new(10)
+            );
+            let leaderboard = |app: &App, limit: Option<u32>| -> Vec<Addr> {
+                app.wrap()
+                    .query_wasm_smart::<Vec<RelayerStats>>(
+                        contract.addr(),
+                        &QueryMsg::GetRelayerLeaderboard { limit },
+                    )
+                    .unwrap()
+                    .into_iter()
+                    .map(|stats| stats.relayer)
+                    .collect()
+            };
+            assert_eq!(leaderboard(&app, None), vec![relayer.clone(), other]);
+            assert_eq!(leaderboard(&app, Some(1)), vec![relayer]);
+        }
+    }
+
+    mod ibc_hook {
+        use std::collections::HashMap;
+
+        use super::*;
+        use crate::{
+            msg::ExecuteMsg,
+            route::{
+                Chain, ChainState, ChainType, Directive, Factor, FeeTokenFactor, TargetChainFactor,
+                Token,
+            },
+            state::{GenerateTicketReq, TxAction},
+        };
+
+        #[test]
+        fn ibc_hook() {
+            use crate::hooks::{intermediate_sender, HookAction};
+
+            let (mut app, contract) = proper_instantiate();
+            setup_escrow_token(&mut app, &contract);
+            let original_sender = "cosmos1qyqszqgpqyqszqgpqyqszqgpqyqszqgpjnp7du";
+            let hook_sender = Addr::unchecked(
+                intermediate_sender(&contract.addr(), "channel-0", original_sender).unwrap(),
+            );
+            let recovery = app.api().addr_make("recovery");
+            app.init_modules(|router, _, storage| {
+                router
+                    .bank
+                    .init_balance(storage, &hook_sender, vec![Coin::new(640u128, OSMO)])
+            })
+            .unwrap();
+            let hook = |channel: &str, amount: &str| ExecuteMsg::IbcHook {
+                channel: channel.to_string(),
+                original_sender: original_sender.to_string(),
+                recovery: recovery.to_string(),
+                action: HookAction::GenerateTicket {
+                    token_id: TOKEN_ID.to_string(),
+                    receiver: "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string(),
+                    amount: amount.to_string(),
+                    target_chain: "Bitcoin".to_string(),
+                    action: TxAction::Transfer,
+                    memo: None,
+                },
+            };
+            let balance =
+                |app: &App, addr: &Addr| app.wrap().query_balance(addr, OSMO).unwrap().amount;
+
+            // the sender must be the intermediate sender of the channel
+            let err = app
+                .execute_contract(
+                    hook_sender.clone(),
+                    contract.addr(),
+                    &hook("channel-1", "300"),
+                    &[Coin::new(320u128, OSMO)],
+                )
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("Unauthorized"));
+            let err = app
+                .execute_contract(
+                    hook_sender.clone(),
+                    contract.addr(),
+                    &ExecuteMsg::ExecuteHook {
+                        original_sender: original_sender.to_string(),
+                        recovery: recovery.clone(),
+                        action: HookAction::RedeemToken {
+                            token_id: TOKEN_ID.to_string(),
+                            receiver: "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string(),
+                            amount: "300".to_string(),
+                            target_chain: "Bitcoin".to_string(),
+                        },
+                    },
+                    &[Coin::new(320u128, OSMO)],
+                )
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("Unauthorized"));
+
+            let res = app
+                .execute_contract(
+                    hook_sender.clone(),
+                    contract.addr(),
+                    &hook("channel-0", "300"),
+                    &[Coin::new(320u128, OSMO)],
+                )
+                .unwrap();
+            let event = res
+                .events
+                .iter()
+                .find(|e| e.ty == "wasm-GenerateTicketRequested")
+                .unwrap();
+            assert!(event
+                .attributes
+                .iter()
+                .any(|a| a.key == "sender" && a.value == original_sender));
+            assert_eq!(balance(&app, &contract.addr()), Uint128::new(320));
+
+            // a failing ticket sends the transfer to the recovery address
+            let res = app
+                .execute_contract(
+                    hook_sender.clone(),
+                    contract.addr(),
+                    &hook("channel-0", "250"),
+                    &[Coin::new(320u128, OSMO)],
+                )
+                .unwrap();
+            assert!(res.events.iter().any(|e| e.ty == "wasm-IbcHookRecovered"));
+            assert_eq!(balance(&app, &recovery), Uint128::new(320));
+            assert_eq!(balance(&app, &contract.addr()), Uint128::new(320));
+            assert_eq!(balance(&app, &hook_sender), Uint128::zero());
+        }
+
+        #[test]
+        fn ibc_hook_minted_token() {
+            use crate::hooks::{intermediate_sender, HookAction};
+
+            let (mut app, contract, denom_admins) = instantiate_with_denom_admins();
+            let token_id = "Ethereum-erc20-USDT";
+            let denom = "factory/osmo1issuer/usdt";
+            denom_admins
+                .0
+                .borrow_mut()
+                .insert(denom.to_string(), contract.addr().to_string());
+            let original_sender = "cosmos1qyqszqgpqyqszqgpqyqszqgpqyqszqgpjnp7du";
+            let hook_sender = Addr::unchecked(
+                intermediate_sender(&contract.addr(), "channel-0", original_sender).unwrap(),
+            );
+            let recovery = app.api().addr_make("recovery");
+            app.init_modules(|router, _, storage| {
+                router
+                    .bank
+                    .init_balance(storage, &hook_sender, vec![Coin::new(620u128, denom)])
+            })
+            .unwrap();
+            let msgs = vec![
+                ExecuteMsg::SetAdoptedDenom {
+                    token_id: token_id.to_string(),
+                    denom: Some(denom.to_string()),
+                },
+                ExecuteMsg::ExecDirective {
+                    seq: 1,
+                    directive: Directive::AddToken(Token {
+                        token_id: token_id.to_string(),
+                        name: "Tether USD".to_string(),
+                        symbol: "USDT".to_string(),
+                        decimals: 6,
+                        icon: None,
+                        metadata: HashMap::new(),
+                    }),
+                },
+                ExecuteMsg::ExecDirective {
+                    seq: 2,
+                    directive: Directive::AddChain(Chain {
+                        chain_id: "Bitcoin".to_string(),
+                        canister_id: "canister".to_string(),
+                        chain_type: ChainType::SettlementChain,
+                        chain_state: ChainState::Active,
+                        contract_address: None,
+                        counterparties: None,
+                        fee_token: None,
+                        receiver_validator: None,
+                    }),
+                },
+                ExecuteMsg::ExecDirective {
+                    seq: 3,
+                    directive: Directive::UpdateFee(Factor::UpdateFeeTokenFactor(FeeTokenFactor {
+                        fee_token: OSMO.to_string(),
+                        fee_token_factor: 10,
+                    })),
+                },
+                ExecuteMsg::ExecDirective {
+                    seq: 4,
+                    directive: Directive::UpdateFee(Factor::UpdateTargetChainFactor(
+                        TargetChainFactor {
+                            target_chain_id: "Bitcoin".to_string(),
+                            target_chain_factor: 2,
+                        },
+                    )),
+                },
+            ];
+            for msg in msgs {
+                app.execute_contract(Addr::unchecked(ADMIN), contract.addr(), &msg, &[])
+                    .unwrap();
+            }
+            let hook = ExecuteMsg::IbcHook {
+                channel: "channel-0".to_string(),
+                original_sender: original_sender.to_string(),
+                recovery: recovery.to_string(),
+                action: HookAction::GenerateTicket {
+                    token_id: token_id.to_string(),
+                    receiver: "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string(),
+                    amount: "300".to_string(),
+                    target_chain: "Bitcoin".to_string(),
+                    action: TxAction::Transfer,
+                    memo: None,
+                },
+            };
+            let balance = |app: &DenomAdminsApp, addr: &Addr| {
+                app.wrap().query_balance(addr, denom).unwrap().amount
+            };
+
+            // the transfer carries no fee token, the token has to take the fee itself
+            let res = app
+                .execute_contract(
+                    hook_sender.clone(),
+                    contract.addr(),
+                    &hook,
+                    &[Coin::new(310u128, denom)],
+                )
+                .unwrap();
+            assert!(res.events.iter().any(|e| e.ty == "wasm-IbcHookRecovered"));
+            assert_eq!(balance(&app, &recovery), Uint128::new(310));
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract.addr(),
+                &ExecuteMsg::SetHookFeeFactor {
+                    token_id: token_id.to_string(),
+                    factor: Some(Uint128::new(5)),
+                },
+                &[],
+            )
+            .unwrap();
+            let res = app
+                .execute_contract(
+                    hook_sender.clone(),
+                    contract.addr(),
+                    &hook,
+                    &[Coin::new(310u128, denom)],
+                )
+                .unwrap();
+            let event = res
+                .events
+                .iter()
+                .find(|e| e.ty == "wasm-GenerateTicketRequested")
+                .unwrap();
+            let req: GenerateTicketReq = serde_json::from_str(
+                &event
+                    .attributes
+                    .iter()
+                    .find(|a| a.key == "generate_ticket_request")
+                    .unwrap()
+                    .value,
+            )
+            .unwrap();
+            assert_eq!(req.sender, original_sender);
+            assert_eq!(req.refund_to, Some(recovery.to_string()));
+            assert_eq!(
+                (req.fee_token.as_str(), req.fee_amount.as_str()),
+                (denom, "10")
+            );
+            // the ticket amount is burned, the fee stays with the contract
+            assert_eq!(balance(&app, &contract.addr()), Uint128::new(10));
+            assert_eq!(balance(&app, &hook_sender), Uint128::zero());
+        }
+    }
+
+    mod ibc_forward {
+        use super::*;
+        use crate::{
+            msg::{ExecuteMsg, QueryMsg},
+            state::TxAction,
+        };
+
+        #[test]
+        fn ibc_forward() {
+            use crate::forward::local_account;
+
+            let (mut app, contract) = proper_instantiate();
+            setup_escrow_token(&mut app, &contract);
+            let user = app.api().addr_make(USER);
+            let route = Addr::unchecked("wasm1rptjktp9md9u2jcjsxe4ehg3pmz5hfxquklwvt");
+            let receiver = "cosmos1qypqxpqpqgpsgqgzqvzqzqsrqsqsyqcyy2wt4y";
+            let beneficiary = local_account(&contract.addr(), receiver).unwrap();
+
+            app.execute_contract(
+                user.clone(),
+                contract.addr(),
+                &ExecuteMsg::GenerateTicket {
+                    token_id: TOKEN_ID.to_string(),
+                    sender: user.to_string(),
+                    receiver: "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string(),
+                    amount: "300".to_string(),
+                    target_chain: "Bitcoin".to_string(),
+                    action: TxAction::Transfer,
+                    memo: None,
+                },
+                &[Coin::new(320u128, OSMO)],
+            )
+            .unwrap();
+
+            let mint = |receiver: &str| ExecuteMsg::PrivilegeMintToken {
+                ticket_id: "ticket".to_string(),
+                token_id: TOKEN_ID.to_string(),
+                receiver: Addr::unchecked(receiver),
+                amount: "100".to_string(),
+                transmuter: None,
+                signature: None,
+                channel: Some("channel-0".to_string()),
+                recovery: None,
+            };
+            let err = app
+                .execute_contract(route.clone(), contract.addr(), &mint("receiver"), &[])
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("InvalidReceiver"));
+
+            // the test chain has no IBC, so the transfer fails and is credited to the receiver's
+            // account on this chain
+            let res = app
+                .execute_contract(route.clone(), contract.addr(), &mint(receiver), &[])
+                .unwrap();
+            assert!(res.events.iter().any(|e| e.ty == "wasm-ForwardFailed"));
+            let claimable: Vec<Coin> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract.addr(),
+                    &QueryMsg::GetClaimable {
+                        address: beneficiary.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(claimable, vec![Coin::new(100u128, OSMO)]);
+            let state: crate::state::State = app
+                .wrap()
+                .query_wasm_smart(contract.addr(), &QueryMsg::GetState {})
+                .unwrap();
+            assert_eq!(state.escrow_balances[TOKEN_ID], 200);
+
+            app.execute_contract(
+                beneficiary.clone(),
+                contract.addr(),
+                &ExecuteMsg::ClaimBalance {},
+                &[],
+            )
+            .unwrap();
+            assert_eq!(
+                app.wrap()
+                    .query_balance(beneficiary.clone(), OSMO)
+                    .unwrap()
+                    .amount,
+                Uint128::new(100)
+            );
+            let err = app
+                .execute_contract(
+                    beneficiary,
+                    contract.addr(),
+                    &ExecuteMsg::ClaimBalance {},
+                    &[],
+                )
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("NoClaimableBalance"));
+
+            // nobody holds the key of a contract receiver, its forward needs a recovery account
+            let forward_to_contract = |recovery: Option<Addr>| ExecuteMsg::PrivilegeMintToken {
+                ticket_id: "ticket-2".to_string(),
+                token_id: TOKEN_ID.to_string(),
+                receiver: Addr::unchecked(
+                    "cosmos14hj2tavq8fpesdwxxcu44rty3hh90vhujrvcmstl4zr3txmfvw9s4hmalr",
+                ),
+                amount: "50".to_string(),
+                transmuter: None,
+                signature: None,
+                channel: Some("channel-0".to_string()),
+                recovery,
+            };
+            let err = app
+                .execute_contract(
+                    route.clone(),
+                    contract.addr(),
+                    &forward_to_contract(None),
+                    &[],
+                )
+                .unwrap_err();
+            assert!(err.root_cause().to_string().contains("InvalidReceiver"));
+            let recovery = app.api().addr_make("recovery");
+            let res = app
+                .execute_contract(
+                    route,
+                    contract.addr(),
+                    &forward_to_contract(Some(recovery.clone())),
+                    &[],
+                )
+                .unwrap();
+            assert!(res.events.iter().any(|e| e.ty == "wasm-ForwardFailed"));
+            app.execute_contract(
+                recovery.clone(),
+                contract.addr(),
+                &ExecuteMsg::ClaimBalance {},
+                &[],
+            )
+            .unwrap();
+            assert_eq!(
+                app.wrap().query_balance(recovery, OSMO).unwrap().amount,
+                Uint128::new(50)
+            );
+        }
+    }
 }
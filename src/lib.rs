@@ -1,10 +1,18 @@
+pub mod address;
 pub mod contract;
+pub mod denom_metadata;
+pub mod forward;
 mod error;
 pub mod helpers;
+pub mod hooks;
+pub mod hub_key;
+pub mod ibc;
 pub mod integration_tests;
+pub mod memo;
 pub mod msg;
 pub mod state;
 pub mod route;
+pub mod token_id;
 pub mod reply;
 pub mod types;
 
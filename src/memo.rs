@@ -0,0 +1,157 @@
+use cosmwasm_std::Attribute;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::ContractError;
+
+/// Memo length applied while the admin has not configured one, in bytes.
+pub const DEFAULT_MAX_MEMO_LENGTH: u32 = 512;
+
+pub const FEE_PAYER_KEY: &str = "fee_payer";
+pub const CALL_KEY: &str = "call";
+pub const REFERRAL_KEY: &str = "referral";
+
+/// Contract the receiver's chain is asked to call once the ticket is delivered.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DownstreamCall {
+    pub contract: String,
+    pub msg: Value,
+}
+
+/// Reserved fields of a structured memo. A memo is structured when it is a JSON object,
+/// keys other than the reserved ones are passed through untouched.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Memo {
+    pub fee_payer: Option<String>,
+    pub call: Option<DownstreamCall>,
+    pub referral: Option<String>,
+}
+
+impl Memo {
+    /// Parses `memo`, returns `None` for a plain text memo.
+    pub fn parse(memo: &str) -> Result<Option<Memo>, ContractError> {
+        if !memo.trim_start().starts_with('{') {
+            return Ok(None);
+        }
+        let fields: Map<String, Value> =
+            serde_json::from_str(memo).map_err(|e| ContractError::InvalidMemo(e.to_string()))?;
+
+        let fee_payer = non_empty_string(&fields, FEE_PAYER_KEY)?;
+        let referral = non_empty_string(&fields, REFERRAL_KEY)?;
+        let call = match fields.get(CALL_KEY) {
+            None | Some(Value::Null) => None,
+            Some(value) => {
+                let call: DownstreamCall = serde_json::from_value(value.clone())
+                    .map_err(|e| ContractError::InvalidMemo(format!("{}: {}", CALL_KEY, e)))?;
+                if call.contract.is_empty() {
+                    return Err(ContractError::InvalidMemo(format!(
+                        "{}.contract is empty",
+                        CALL_KEY
+                    )));
+                }
+                Some(call)
+            }
+        };
+
+        Ok(Some(Memo {
+            fee_payer,
+            call,
+            referral,
+        }))
+    }
+
+    /// The reserved fields as separate event attributes, absent fields are left out.
+    pub fn attributes(&self) -> Result<Vec<Attribute>, ContractError> {
+        let mut attributes = vec![];
+        if let Some(fee_payer) = &self.fee_payer {
+            attributes.push(Attribute::new("memo_fee_payer", fee_payer));
+        }
+        if let Some(call) = &self.call {
+            attributes.push(Attribute::new("memo_call_contract", &call.contract));
+            attributes.push(Attribute::new(
+                "memo_call_msg",
+                serde_json::to_string(&call.msg)
+                    .map_err(|e| ContractError::CustomError(e.to_string()))?,
+            ));
+        }
+        if let Some(referral) = &self.referral {
+            attributes.push(Attribute::new("memo_referral", referral));
+        }
+        Ok(attributes)
+    }
+}
+
+/// Checks the memo size against `max_length` and parses it when it is structured.
+pub fn validate_memo(memo: &str, max_length: u32) -> Result<Option<Memo>, ContractError> {
+    if memo.len() > max_length as usize {
+        return Err(ContractError::MemoTooLong(memo.len(), max_length));
+    }
+    Memo::parse(memo)
+}
+
+fn non_empty_string(
+    fields: &Map<String, Value>,
+    key: &str,
+) -> Result<Option<String>, ContractError> {
+    match fields.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(value)) if !value.is_empty() => Ok(Some(value.clone())),
+        Some(_) => Err(ContractError::InvalidMemo(format!(
+            "{} must be a non-empty string",
+            key
+        ))),
+    }
+}
+
+#[test]
+pub fn test_plain_memo() {
+    assert_eq!(validate_memo("hello omnity", 64).unwrap(), None);
+    assert_eq!(validate_memo("", 64).unwrap(), None);
+    assert!(matches!(
+        validate_memo(&"a".repeat(65), 64),
+        Err(ContractError::MemoTooLong(65, 64))
+    ));
+}
+
+#[test]
+pub fn test_structured_memo() {
+    let memo = r#"{"fee_payer":"osmo1payer","call":{"contract":"0xabc","msg":{"swap":{}}},"referral":"friend","note":"kept"}"#;
+    let parsed = validate_memo(memo, DEFAULT_MAX_MEMO_LENGTH)
+        .unwrap()
+        .unwrap();
+    assert_eq!(parsed.fee_payer, Some("osmo1payer".to_string()));
+    assert_eq!(parsed.referral, Some("friend".to_string()));
+    assert_eq!(parsed.call.as_ref().unwrap().contract, "0xabc");
+
+    let attributes = parsed.attributes().unwrap();
+    assert_eq!(attributes.len(), 4);
+    assert_eq!(
+        attributes[2],
+        Attribute::new("memo_call_msg", r#"{"swap":{}}"#)
+    );
+
+    assert_eq!(
+        validate_memo("{}", DEFAULT_MAX_MEMO_LENGTH).unwrap(),
+        Some(Memo::default())
+    );
+}
+
+#[test]
+pub fn test_invalid_structured_memo() {
+    for memo in [
+        "{not json",
+        r#"{"fee_payer":1}"#,
+        r#"{"referral":""}"#,
+        r#"{"call":{"contract":"0xabc"}}"#,
+        r#"{"call":{"contract":"","msg":{}}}"#,
+    ] {
+        assert!(
+            matches!(
+                validate_memo(memo, DEFAULT_MAX_MEMO_LENGTH),
+                Err(ContractError::InvalidMemo(_))
+            ),
+            "{}",
+            memo
+        );
+    }
+}
@@ -1,9 +1,20 @@
 use std::collections::BTreeMap;
 
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, Binary, Coin, Uint128};
 
-use crate::{route::{Directive, Token}, state::{State, TxAction}};
+use crate::{
+    address::ReceiverValidator,
+    forward::{Forward, IbcLifecycleComplete},
+    hooks::HookAction,
+    route::{Directive, Token},
+    state::{
+        Approval, ArchivedToken, AttestorSet, CounterpartPort, GenerateTicketReq, IcpChainKeyToken,
+        MintAttestation, PendingOperation, RelayerStats, State, TokenMetadataChange, TokenRoutes,
+        TxAction,
+    },
+    ContractError,
+};
 
 pub mod reply_msg_id {
     pub const REDEEM_REPLY_ID: u64 = 1;
@@ -12,6 +23,8 @@ pub mod reply_msg_id {
     pub const SWAP_CKBTC_TO_ALLBTC_REPLY_ID: u64 = 4;
     pub const SWAP_ALLBTC_TO_CKBTC_REPLY_ID: u64 = 5;
     pub const SEND_ALLBTC_REPLY_ID: u64 = 6;
+    pub const IBC_HOOK_REPLY_ID: u64 = 7;
+    pub const IBC_FORWARD_REPLY_ID: u64 = 8;
 }
 
 #[cw_serde]
@@ -26,13 +39,29 @@ pub enum ExecuteMsg {
         seq: u64,
         directive: Directive,
     },
+    // a directive signed by the hub key, anyone may relay it. Required once a hub key is
+    // registered
+    ExecSignedDirective {
+        seq: u64,
+        // JSON encoded `Directive`, verified as signed before it is parsed
+        directive: Binary,
+        signature: Binary,
+    },
     PrivilegeMintToken {
         ticket_id: String,
         token_id: String,
         receiver: Addr,
         amount: String,
         // transmuter token into another token then send to user
-        transmuter: Option<String>
+        transmuter: Option<String>,
+        // signature of the ticket by the hub key, lets anyone submit it
+        signature: Option<Binary>,
+        // forward the tokens over this channel, `receiver` being an address on its other end
+        #[serde(default)]
+        channel: Option<String>,
+        // local account a failed forward is credited to, required for contract receivers
+        #[serde(default)]
+        recovery: Option<Addr>,
     },
     RedeemToken {
         token_id: String,
@@ -54,6 +83,23 @@ pub enum ExecuteMsg {
         action: TxAction,
         memo: Option<String>,
     },
+    // wasm memo of an ICS-20 transfer, executed by ibc-hooks from the intermediate sender of
+    // `original_sender` on `channel`. The funds go to `recovery` if the ticket fails
+    IbcHook {
+        channel: String,
+        original_sender: String,
+        recovery: String,
+        action: HookAction,
+    },
+    // the ticket of an ibc hook, only called by the contract itself
+    ExecuteHook {
+        original_sender: String,
+        recovery: Addr,
+        action: HookAction,
+    },
+    // pay the coins of failed forwards credited to the signer
+    ClaimBalance {},
+    // timelocked
     UpdateRoute {
         route: Addr,
     },
@@ -69,6 +115,91 @@ pub enum ExecuteMsg {
         decimals: u8,
         icon: Option<String>,
     },
+    // allow `operator` to generate tickets on behalf of the signer, until `expires_at` (seconds)
+    Approve {
+        operator: String,
+        expires_at: Option<u64>,
+    },
+    RevokeApproval {
+        operator: String,
+    },
+    // override the ICP chain-key asset a token redeems on its settlement chain, otherwise named
+    // by the token id. None clears the override
+    SetChainKeyToken {
+        token_id: String,
+        chain_key_token: Option<IcpChainKeyToken>,
+    },
+    // enforce the receiver address format of a counterparty chain, none disables the check
+    SetReceiverValidator {
+        chain_id: String,
+        validator: Option<ReceiverValidator>,
+    },
+    // restrict the target chains of a token, none lifts the restriction
+    SetTokenRoutes {
+        token_id: String,
+        routes: Option<TokenRoutes>,
+    },
+    // fee factor of ibc hook tickets of a token paying the fee in the token itself, none
+    // requires the fee token
+    SetHookFeeFactor {
+        token_id: String,
+        factor: Option<Uint128>,
+    },
+    // memo size limit in bytes, none restores the default
+    SetMaxMemoLength {
+        max_memo_length: Option<u32>,
+    },
+    // bridge a native denom by locking it instead of minting, none switches back to minting
+    SetEscrowToken {
+        token_id: String,
+        denom: Option<String>,
+    },
+    // mint an existing tokenfactory denom administered by the contract instead of creating
+    // one, none switches back to a denom of its own
+    SetAdoptedDenom {
+        token_id: String,
+        denom: Option<String>,
+    },
+    // schedule handing the denom admin of a minted token to `new_admin`, timelocked
+    TransferDenomAdmin {
+        token_id: String,
+        new_admin: String,
+    },
+    // schedule handing the denom admin of every minted token to `new_admin`, timelocked
+    TransferAllDenomAdmins {
+        new_admin: String,
+    },
+    // schedule a new timelock delay in seconds of at least `MIN_TIMELOCK_SECS`, none restores
+    // the default
+    SetTimelockDelay {
+        delay_secs: Option<u64>,
+    },
+    // schedule a new reward for relaying a hub-signed ticket, none stops rewards
+    SetRelayReward {
+        reward: Option<Uint128>,
+    },
+    // pay the rewards still owed to the signer, as far as collected fees allow
+    ClaimRelayRewards {},
+    // schedule the counterpart port allowed to open an IBC channel, none closes the channel
+    SetIbcPort {
+        port: Option<CounterpartPort>,
+    },
+    // schedule requiring a threshold of attestors for mints, none switches back to the route
+    SetAttestors {
+        attestors: Option<AttestorSet>,
+    },
+    // run a scheduled operation once its timelock has passed
+    ExecuteOperation {
+        id: u64,
+    },
+    CancelOperation {
+        id: u64,
+    },
+    // archive a removed token once its sunset is over, open to anyone
+    ArchiveToken {
+        token_id: String,
+    },
+    // timelocked
     RefundToken {
         denom: String,
         receiver: String,
@@ -76,6 +207,12 @@ pub enum ExecuteMsg {
     }
 }
 
+#[cw_serde]
+pub enum SudoMsg {
+    // outcome of a transfer sent with an `ibc_callback` memo, from ibc-hooks
+    IbcLifecycleComplete(IbcLifecycleComplete),
+}
+
 #[cw_serde]
 pub enum ToggleAction {
     Activate,
@@ -89,12 +226,96 @@ pub enum QueryMsg {
     GetState {},
     #[returns(GetTokenResponse)]
     GetTokenList {},
+    // the token with its full metadata, runes ids may use either `•` or `.`
+    #[returns(Option<Token>)]
+    GetToken { token_id: String },
+    // bank denom minted, burnt or locked for the token
+    #[returns(Option<String>)]
+    GetTokenDenom { token_id: String },
+    // coins of failed forwards claimable by `address`
+    #[returns(Vec<Coin>)]
+    GetClaimable { address: String },
+    // forward awaiting the outcome of its transfer
+    #[returns(Option<Forward>)]
+    GetForward { channel: String, sequence: u64 },
+    #[returns(Option<RelayerStats>)]
+    GetRelayer { relayer: String },
+    // relayers with the most tickets first, at most `limit`, ties by address descending
+    #[returns(Vec<RelayerStats>)]
+    GetRelayerLeaderboard { limit: Option<u32> },
+    // partial attestations of a mint ticket, one per distinct attested mint
+    #[returns(Vec<MintAttestation>)]
+    GetMintAttestations { ticket_id: String },
+    #[returns(Option<PendingOperation>)]
+    GetPendingOperation { id: u64 },
+    // oldest first, at most `limit` operations after `start_after`
+    #[returns(Vec<PendingOperation>)]
+    GetPendingOperations {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // a removed token once archived, with the denom and where its admin went
+    #[returns(Option<ArchivedToken>)]
+    GetArchivedToken { token_id: String },
+    // oldest first, at most `limit` changes after `start_after`
+    #[returns(Vec<TokenMetadataChange>)]
+    GetTokenMetadataHistory {
+        token_id: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
     #[returns(GetFeeResponse)]
     GetFeeInfo {},
     #[returns(GetTargetChainFeeResponse)]
     GetTargetChainFee {
         target_chain: String,
     },
+    #[returns(QuoteRedeemResponse)]
+    QuoteRedeem {
+        token_id: String,
+        receiver: String,
+        target_chain: String,
+        amount: String,
+        sender: String,
+    },
+    // active counterparty chains a ticket of `token_id` can be sent to
+    #[returns(Vec<String>)]
+    GetReachableChains { token_id: String },
+    #[returns(Option<Approval>)]
+    GetApproval { sender: String, operator: String },
+    #[returns(Vec<Approval>)]
+    GetApprovals { sender: String },
+    // dry runs of the matching execute messages, `sender` and `funds` stand in for the
+    // message info and the returned ticket is the one the execution would produce
+    #[returns(GenerateTicketReq)]
+    SimulateRedeem {
+        sender: String,
+        funds: Vec<Coin>,
+        token_id: String,
+        receiver: String,
+        amount: String,
+        target_chain: String,
+    },
+    #[returns(GenerateTicketReq)]
+    SimulateRedeemAllBTC {
+        sender: String,
+        funds: Vec<Coin>,
+        receiver: String,
+        amount: String,
+        target_chain: String,
+    },
+    #[returns(GenerateTicketReq)]
+    SimulateGenerateTicket {
+        signer: String,
+        funds: Vec<Coin>,
+        token_id: String,
+        sender: String,
+        receiver: String,
+        amount: String,
+        target_chain: String,
+        action: TxAction,
+        memo: Option<String>,
+    },
 }
 
 #[cw_serde]
@@ -105,6 +326,61 @@ pub struct GetTargetChainFeeResponse {
     pub fee_amount: Option<u128>,
 }
 
+#[cw_serde]
+pub struct QuoteRedeemResponse {
+    // omnity id of the token, as the ticket carries it
+    pub token_id: String,
+    pub target_chain: String,
+    pub amount: String,
+    pub sender: String,
+    pub fee_token: Option<String>,
+    pub fee_amount: Option<u128>,
+    pub min_amount: String,
+    // sender balance of the token denom, none if the token is unknown
+    pub balance: Option<String>,
+    pub chain_active: bool,
+    // empty when the redeem would pass every check
+    pub errors: Vec<RedeemCheckError>,
+}
+
+#[cw_serde]
+pub enum RedeemCheckError {
+    ChainDeactive,
+    TokenNotFound,
+    TokenRemoved,
+    TargetChainNotFound,
+    TargetChainDeactive,
+    TargetChainUnreachable,
+    TokenRouteNotAllowed,
+    FeeHasNotSet,
+    InvalidAmount { amount: String },
+    RedeemAmountLessThanMinAmount { min_amount: String },
+    InsufficientBalance { balance: String },
+    Other { message: String },
+}
+
+impl From<ContractError> for RedeemCheckError {
+    fn from(err: ContractError) -> Self {
+        match err {
+            ContractError::ChainDeactive => Self::ChainDeactive,
+            ContractError::TokenNotFound => Self::TokenNotFound,
+            ContractError::TokenRemoved => Self::TokenRemoved,
+            ContractError::TargetChainNotFound => Self::TargetChainNotFound,
+            ContractError::TargetChainDeactive => Self::TargetChainDeactive,
+            ContractError::TargetChainUnreachable => Self::TargetChainUnreachable,
+            ContractError::TokenRouteNotAllowed => Self::TokenRouteNotAllowed,
+            ContractError::FeeHasNotSet => Self::FeeHasNotSet,
+            ContractError::InvalidAmount(amount) => Self::InvalidAmount { amount },
+            ContractError::RedeemAmountLessThanMinAmount(min_amount, _) => {
+                Self::RedeemAmountLessThanMinAmount { min_amount }
+            }
+            e => Self::Other {
+                message: e.to_string(),
+            },
+        }
+    }
+}
+
 // We define a custom struct for each query response
 #[cw_serde]
 pub struct GetTokenResponse {
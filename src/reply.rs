@@ -1,14 +1,21 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    Attribute, BankMsg, Binary, CosmosMsg, DepsMut, Env, Event, Reply, Response, SubMsg, Uint128,
+    Attribute, BankMsg, CosmosMsg, DepsMut, Env, Event, Reply, Response, Storage, SubMsg,
+    Uint128,
 };
 use osmosis_std::types::osmosis::poolmanager::v1beta1::{MsgSwapExactAmountIn, SwapAmountInRoute};
 
 use crate::{
-    contract::execute::{build_burn_msg, token_denom},
+    contract::execute::{
+        build_burn_msg, next_ticket_seq, redeem_allbtc_ticket_req, stargate_msg,
+    },
+    forward::reply_forward,
+    hooks::HookRecovery,
+    ibc::deliver_ticket,
+    memo::Memo,
     msg::reply_msg_id,
-    state::{read_state, GenerateTicketReq, IcpChainKeyToken, GENERATE_TICKET_REQ, STATE},
+    state::{read_state, GenerateTicketReq, GENERATE_TICKET_REQ},
     types::{MintTokenPayload, RedeemAllBTC},
     ContractError,
 };
@@ -25,71 +32,19 @@ pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractEr
 
 pub fn reply_success(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
     match msg.id {
+        reply_msg_id::IBC_FORWARD_REPLY_ID => reply_forward(deps.storage, msg),
         reply_msg_id::REDEEM_REPLY_ID => {
             let generate_ticket_req: GenerateTicketReq =
                 serde_json::from_slice(msg.payload.clone().as_slice())
                     .map_err(|e| ContractError::CustomError(e.to_string()))?;
-            let req_str = serde_json::to_string(&generate_ticket_req).unwrap();
-            GENERATE_TICKET_REQ.save(
-                deps.storage,
-                generate_ticket_req.seq,
-                &generate_ticket_req,
-            )?;
-            Ok(Response::new()
-                .add_event(Event::new("RedeemRequested").add_attributes(vec![
-                    Attribute::new("token_id", generate_ticket_req.token_id.clone()),
-                    Attribute::new("sender", generate_ticket_req.sender.clone()),
-                    Attribute::new("receiver", generate_ticket_req.receiver.clone()),
-                    Attribute::new("amount", generate_ticket_req.amount.clone()),
-                    Attribute::new("target_chain", generate_ticket_req.target_chain_id.clone()),
-                ]))
-                .add_event(Event::new("GenerateTicketRequested").add_attributes(vec![
-                        Attribute::new("generate_ticket_request", req_str),
-                        Attribute::new("seq", generate_ticket_req.seq.to_string()),
-                        Attribute::new("target_chain_id", generate_ticket_req.target_chain_id),
-                        Attribute::new("sender", generate_ticket_req.sender),
-                        Attribute::new("receiver", generate_ticket_req.receiver),
-                        Attribute::new("token_id", generate_ticket_req.token_id),
-                        Attribute::new("amount", generate_ticket_req.amount),
-                        Attribute::new(
-                            "action",
-                            serde_json::to_string(&generate_ticket_req.action)
-                                .map_err(|e| ContractError::CustomError(e.to_string()))?,
-                        ),
-                        Attribute::new("timestamp", generate_ticket_req.timestamp.to_string()),
-                        Attribute::new("memo", generate_ticket_req.memo.unwrap_or("".to_string())),
-                    ])))
+            let response = Response::new().add_event(redeem_requested_event(&generate_ticket_req));
+            deliver_ticket(deps.storage, &env, response, generate_ticket_req)
         }
         reply_msg_id::GENERATE_TICKET_REPLY_ID => {
             let generate_ticket_req: GenerateTicketReq =
                 serde_json::from_slice(msg.payload.clone().as_slice())
                     .map_err(|e| ContractError::CustomError(e.to_string()))?;
-            let req_str = serde_json::to_string(&generate_ticket_req).unwrap();
-            GENERATE_TICKET_REQ.save(
-                deps.storage,
-                generate_ticket_req.seq,
-                &generate_ticket_req,
-            )?;
-            Ok(
-                Response::new().add_event(Event::new("GenerateTicketRequested").add_attributes(
-                    vec![
-                    Attribute::new("generate_ticket_request", req_str),
-                    Attribute::new("seq", generate_ticket_req.seq.to_string()),
-                    Attribute::new("target_chain_id", generate_ticket_req.target_chain_id),
-                    Attribute::new("sender", generate_ticket_req.sender),
-                    Attribute::new("receiver", generate_ticket_req.receiver),
-                    Attribute::new("token_id", generate_ticket_req.token_id),
-                    Attribute::new("amount", generate_ticket_req.amount),
-                    Attribute::new(
-                        "action",
-                        serde_json::to_string(&generate_ticket_req.action)
-                            .map_err(|e| ContractError::CustomError(e.to_string()))?,
-                    ),
-                    Attribute::new("timestamp", generate_ticket_req.timestamp.to_string()),
-                    Attribute::new("memo", generate_ticket_req.memo.unwrap_or("".to_string())),
-                ],
-                )),
-            )
+            deliver_ticket(deps.storage, &env, Response::new(), generate_ticket_req)
         }
         reply_msg_id::MINT_TOKEN_REPLY_ID => {
             // swap cbtc to alloy btc
@@ -123,12 +78,73 @@ pub fn reply_success(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, Co
     }
 }
 
-pub fn reply_error(_deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+pub fn redeem_requested_event(generate_ticket_req: &GenerateTicketReq) -> Event {
+    Event::new("RedeemRequested").add_attributes(vec![
+        Attribute::new("token_id", generate_ticket_req.token_id.clone()),
+        Attribute::new("sender", generate_ticket_req.sender.clone()),
+        Attribute::new("receiver", generate_ticket_req.receiver.clone()),
+        Attribute::new("amount", generate_ticket_req.amount.clone()),
+        Attribute::new("target_chain", generate_ticket_req.target_chain_id.clone()),
+    ])
+}
+
+/// Records the ticket and builds the `GenerateTicketRequested` event the route watches for.
+pub fn generate_ticket_requested_event(
+    storage: &mut dyn Storage,
+    generate_ticket_req: GenerateTicketReq,
+) -> Result<Event, ContractError> {
+    let req_str = serde_json::to_string(&generate_ticket_req)
+        .map_err(|e| ContractError::CustomError(e.to_string()))?;
+    GENERATE_TICKET_REQ.save(storage, generate_ticket_req.seq, &generate_ticket_req)?;
+    // the memo was validated when the ticket was prepared
+    let memo_attributes = match generate_ticket_req.memo.as_deref().map(Memo::parse) {
+        Some(memo) => memo?.map(|memo| memo.attributes()).transpose()?,
+        None => None,
+    };
+    let event = Event::new("GenerateTicketRequested").add_attributes(vec![
+        Attribute::new("generate_ticket_request", req_str),
+        Attribute::new("seq", generate_ticket_req.seq.to_string()),
+        Attribute::new("target_chain_id", generate_ticket_req.target_chain_id),
+        Attribute::new("sender", generate_ticket_req.sender),
+        Attribute::new("receiver", generate_ticket_req.receiver),
+        Attribute::new("token_id", generate_ticket_req.token_id),
+        Attribute::new("amount", generate_ticket_req.amount),
+        Attribute::new(
+            "action",
+            serde_json::to_string(&generate_ticket_req.action)
+                .map_err(|e| ContractError::CustomError(e.to_string()))?,
+        ),
+        Attribute::new("timestamp", generate_ticket_req.timestamp.to_string()),
+        Attribute::new("memo", generate_ticket_req.memo.unwrap_or("".to_string())),
+    ]);
+    Ok(event.add_attributes(memo_attributes.unwrap_or_default()))
+}
+
+pub fn reply_error(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
     match msg.id {
+        reply_msg_id::IBC_FORWARD_REPLY_ID => reply_forward(deps.storage, msg),
         reply_msg_id::REDEEM_REPLY_ID => Ok(Response::new().add_event(Event::new("RedeemFailed"))),
         reply_msg_id::GENERATE_TICKET_REPLY_ID => {
             Ok(Response::new().add_event(Event::new("GenerateTicketFailed")))
         }
+        reply_msg_id::IBC_HOOK_REPLY_ID => {
+            let recovery: HookRecovery = serde_json::from_slice(msg.payload.as_slice())
+                .map_err(|e| ContractError::CustomError(e.to_string()))?;
+            let mut response =
+                Response::new().add_event(Event::new("IbcHookRecovered").add_attributes(vec![
+                    Attribute::new("channel", recovery.channel),
+                    Attribute::new("original_sender", recovery.original_sender),
+                    Attribute::new("recovery", recovery.recovery.clone()),
+                    Attribute::new("funds", format!("{:?}", recovery.funds)),
+                ]));
+            if !recovery.funds.is_empty() {
+                response = response.add_message(BankMsg::Send {
+                    to_address: recovery.recovery.to_string(),
+                    amount: recovery.funds,
+                });
+            }
+            Ok(response)
+        }
 
         reply_msg_id::SWAP_CKBTC_TO_ALLBTC_REPLY_ID => {
             let mint_ckbtc: MintTokenPayload = serde_json::from_slice(msg.payload.as_slice())
@@ -175,10 +191,9 @@ pub fn reply_mint_token(deps: DepsMut, env: Env, msg: Reply) -> Result<Response,
         )
     });
 
-    let mint_token_denom = token_denom(
-        env.contract.address.to_string(),
-        mint_token_payload.token_id.clone(),
-    );
+    let mint_token_denom = read_state(deps.storage, |s| {
+        s.token_denom(&mint_token_payload.token_id)
+    })?;
 
     if mint_token_payload.transmuter.is_none() {
         return Ok(
@@ -218,10 +233,10 @@ pub fn reply_mint_token(deps: DepsMut, env: Env, msg: Reply) -> Result<Response,
         token_out_min_amount: mint_token_payload.amount.clone(),
     };
 
-    let cosmos_msg = CosmosMsg::Stargate {
-        type_url: "/osmosis.poolmanager.v1beta1.MsgSwapExactAmountIn".into(),
-        value: Binary::new(msg.to_proto_bytes()),
-    };
+    let cosmos_msg = stargate_msg(
+        "/osmosis.poolmanager.v1beta1.MsgSwapExactAmountIn",
+        msg.to_proto_bytes(),
+    );
 
     Ok(Response::new().add_submessage(
         SubMsg::reply_always(cosmos_msg, reply_msg_id::SWAP_CKBTC_TO_ALLBTC_REPLY_ID)
@@ -248,7 +263,7 @@ fn reply_swap_allbtc_to_ckbtc(
         )
     });
 
-    let ckbtc_denom = token_denom(env.contract.address.to_string(), ckbtc_token_id.clone());
+    let ckbtc_denom = read_state(deps.storage, |s| s.token_denom(&ckbtc_token_id))?;
 
     // redeem ckbtc
     let burn_msg = build_burn_msg(
@@ -258,25 +273,8 @@ fn reply_swap_allbtc_to_ckbtc(
         redeem_allbtc.amount.clone(),
     );
 
-    let mut state = STATE.load(deps.storage).expect("State not initialized!");
-    let current_seq = state.generate_ticket_sequence;
-    state.generate_ticket_sequence += 1;
-    STATE.save(deps.storage, &state)?;
-
-    let req = GenerateTicketReq {
-        seq: current_seq,
-        target_chain_id: redeem_allbtc.target_chain,
-        sender: redeem_allbtc.sender,
-        receiver: redeem_allbtc.receiver,
-        token_id: ckbtc_token_id,
-        amount: redeem_allbtc.amount.clone(),
-        action: crate::state::TxAction::RedeemIcpChainKeyAssets(IcpChainKeyToken::CKBTC),
-        timestamp: env.block.time.nanos(),
-        block_height: env.block.height,
-        memo: None,
-        fee_token: redeem_allbtc.fee_token,
-        fee_amount: redeem_allbtc.fee_amount,
-    };
+    let current_seq = next_ticket_seq(deps.storage)?;
+    let req = redeem_allbtc_ticket_req(deps.storage, &env, current_seq, redeem_allbtc);
 
     Ok(Response::new().add_submessage(
         SubMsg::reply_on_success(burn_msg, reply_msg_id::REDEEM_REPLY_ID).with_payload(
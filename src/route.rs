@@ -1,6 +1,9 @@
 use std::collections::HashMap;
 use crate::*;
 
+use crate::address::ReceiverValidator;
+use crate::hub_key::HubKey;
+
 pub type ChainId = String;
 pub type TokenId = String;
 
@@ -16,16 +19,82 @@ pub struct Token {
 }
 impl Eq for Token {}
 
+/// `Token::metadata` keys carried into the bank denom metadata, other keys are only stored.
+pub mod metadata_key {
+    pub const DESCRIPTION: &str = "description";
+    pub const URI_HASH: &str = "uri_hash";
+    pub const RUNE_ID: &str = "rune_id";
+    pub const ETCH_TXID: &str = "etch_txid";
+    pub const SETTLEMENT_CHAIN: &str = "settlement_chain";
+    pub const COINGECKO_ID: &str = "coingecko_id";
+    /// JSON array of extra display units, see `denom_metadata::DisplayUnit`.
+    pub const DENOM_UNITS: &str = "denom_units";
+
+    /// Keys published as `<key>:<value>` aliases of the display denom unit.
+    pub const ALIASES: [&str; 4] = [RUNE_ID, ETCH_TXID, SETTLEMENT_CHAIN, COINGECKO_ID];
+}
+
+impl Token {
+    fn metadata_value(&self, key: &str) -> Option<&String> {
+        self.metadata.get(key).filter(|value| !value.is_empty())
+    }
+
+    pub fn description(&self) -> String {
+        self.metadata_value(metadata_key::DESCRIPTION)
+            .unwrap_or(&self.name)
+            .clone()
+    }
+
+    /// Chain the token was issued on, when the route recorded it in the metadata.
+    pub fn settlement_chain(&self) -> Option<&str> {
+        self.metadata_value(metadata_key::SETTLEMENT_CHAIN)
+            .map(String::as_str)
+    }
+
+    pub fn uri_hash(&self) -> String {
+        self.metadata_value(metadata_key::URI_HASH)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn denom_aliases(&self) -> Vec<String> {
+        metadata_key::ALIASES
+            .iter()
+            .filter_map(|key| {
+                self.metadata_value(key)
+                    .map(|value| format!("{}:{}", key, value))
+            })
+            .collect()
+    }
+}
+
 #[cw_serde]
 pub enum Directive {
     AddChain(Chain),
     AddToken(Token),
     UpdateChain(Chain),
     UpdateToken(Token),
+    RemoveToken(RemoveToken),
+    // rotate the key signing mint tickets once the timelock has passed, none only accepts
+    // tickets from the route again
+    UpdateHubKey(Option<HubKey>),
     ToggleChainState(ToggleState),
     UpdateFee(Factor),
 }
 
+#[cw_serde]
+pub struct RemoveToken {
+    pub token_id: TokenId,
+    // redeems stay open this many seconds after the removal, none closes them at once
+    pub sunset_secs: Option<u64>,
+    // receives the denom admin once the token is archived, none hands it to the contract admin
+    pub denom_admin: Option<String>,
+    // receives the coins an escrowed token still locks once it is archived, none keeps them
+    // in the contract
+    #[serde(default)]
+    pub escrow_receiver: Option<String>,
+}
+
 #[cw_serde]
 pub struct Chain {
     pub chain_id: ChainId,
@@ -41,6 +110,9 @@ pub struct Chain {
     pub counterparties: Option<Vec<ChainId>>,
     // fee token
     pub fee_token: Option<TokenId>,
+    // address format of receivers on the chain, kept by updates that leave it out
+    #[serde(default)]
+    pub receiver_validator: Option<ReceiverValidator>,
 }
 
 // impl PartialEq for Chain {
@@ -104,6 +176,32 @@ pub struct FeeTokenFactor {
     pub fee_token_factor: u128,
 }
 
+#[test]
+pub fn test_token_denom_metadata() {
+    let mut token = Token {
+        token_id: "Bitcoin-runes-HOPE.YOU.GET.RICH".to_string(),
+        name: "HOPE•YOU•GET•RICH".to_string(),
+        symbol: "RICH".to_string(),
+        decimals: 2,
+        icon: None,
+        metadata: HashMap::new(),
+    };
+    assert_eq!(token.description(), token.name);
+    assert_eq!(token.uri_hash(), "");
+    assert!(token.denom_aliases().is_empty());
+
+    token.metadata = HashMap::from([
+        (metadata_key::DESCRIPTION.to_string(), "a rune".to_string()),
+        (metadata_key::URI_HASH.to_string(), "abcd".to_string()),
+        (metadata_key::RUNE_ID.to_string(), "840000:3".to_string()),
+        (metadata_key::COINGECKO_ID.to_string(), "".to_string()),
+        ("unknown".to_string(), "kept".to_string()),
+    ]);
+    assert_eq!(token.description(), "a rune");
+    assert_eq!(token.uri_hash(), "abcd");
+    assert_eq!(token.denom_aliases(), vec!["rune_id:840000:3".to_string()]);
+}
+
 #[test]
 pub fn test_update_fee() {
     use msg::ExecuteMsg;
@@ -4,9 +4,14 @@ use cosmwasm_schema::cw_serde;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Storage};
+use cosmwasm_std::{Addr, Coin, Env, HexBinary, Storage, Uint128};
+use sha2::{Digest, Sha256};
 
+use crate::hub_key::HubKey;
+use crate::memo::DEFAULT_MAX_MEMO_LENGTH;
 use crate::route::{Chain, ChainId, ChainState, Token, TokenId};
+use crate::token_id::TokenIdMap;
+use crate::ContractError;
 use cw_storage_plus::{Map, Item};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -32,23 +37,246 @@ pub struct State {
     pub allbtc_token_denom: String, 
     #[serde(default)]
     pub allbtc_swap_pool_id: u64, 
-    // key is replaced id, value is original id
+    // legacy mapping of runes ids (original id -> replaced id), moved into `token_ids` by
+    // `migrate`
     #[serde(default)]
-    pub runes_replaced_id_map: HashMap<String, String>
+    pub runes_replaced_id_map: HashMap<String, String>,
+    // omnity token ids and the local ids tokens are stored under
+    #[serde(default)]
+    pub token_ids: TokenIdMap,
+    // bank denom of each token: its factory denom, or the native denom of an escrowed token
+    #[serde(default)]
+    pub token_denoms: BTreeMap<TokenId, String>,
+    // ICP chain-key asset redeemed when a token is sent home through `RedeemToken`, overriding
+    // the asset named by the token id
+    #[serde(default)]
+    pub chain_key_tokens: BTreeMap<TokenId, IcpChainKeyToken>,
+    // native denoms bridged by locking them in the contract instead of minting, by token id
+    #[serde(default)]
+    pub escrow_tokens: BTreeMap<TokenId, String>,
+    // amount of each escrowed token currently locked by the contract
+    #[serde(default)]
+    pub escrow_balances: BTreeMap<TokenId, u128>,
+    // existing tokenfactory denoms the contract was made admin of, minted and burnt in place
+    // of a denom of its own, by token id
+    #[serde(default)]
+    pub adopted_denoms: BTreeMap<TokenId, String>,
+    // memo size limit in bytes, none falls back to `memo::DEFAULT_MAX_MEMO_LENGTH`
+    #[serde(default)]
+    pub max_memo_length: Option<u32>,
+    // target chains each token may be sent to, tokens without an entry may go anywhere
+    #[serde(default)]
+    pub token_routes: BTreeMap<TokenId, TokenRoutes>,
+    // tokens removed by the route, no longer minted and redeemable until their sunset ends
+    #[serde(default)]
+    pub removed_tokens: BTreeMap<TokenId, TokenSunset>,
+    // id given to the next timelocked operation
+    #[serde(default)]
+    pub operation_sequence: u64,
+    // delay of timelocked operations in seconds, none falls back to `DEFAULT_TIMELOCK_SECS`
+    #[serde(default)]
+    pub timelock_delay: Option<u64>,
+    // when set, mints need the attestation of a threshold of attestors instead of the route
+    #[serde(default)]
+    pub attestors: Option<AttestorSet>,
+    // key of the Omnity hub, mint tickets it signs may be submitted by anyone
+    #[serde(default)]
+    pub hub_key: Option<HubKey>,
+    // fee token amount earned by the relayer of each hub-signed ticket
+    #[serde(default)]
+    pub relay_reward: Option<Uint128>,
+    // port of the counterpart allowed to open an IBC channel with this port
+    #[serde(default)]
+    pub ibc_port: Option<CounterpartPort>,
+    // open channel to the counterpart, tickets are sent over it instead of only as events
+    #[serde(default)]
+    pub ibc_channel: Option<String>,
+    // fee factor of tokens whose ibc hook tickets pay the fee in the transferred coin, an
+    // ICS-20 transfer carries no fee token besides it
+    #[serde(default)]
+    pub hook_fee_factors: BTreeMap<TokenId, Uint128>,
+    // coins still locked for escrowed tokens when they were archived, by token id. They back
+    // tokens held on other chains and are only paid out to the receiver named by the hub
+    #[serde(default)]
+    pub archived_escrow: BTreeMap<TokenId, Coin>,
 }
 
 impl State {
-    pub fn replace_token_id_if_runes(&mut self, token_id: &str) -> String {
-        if let Some(runes_token_id) = self.runes_replaced_id_map.get(token_id) {
-            runes_token_id.to_string()
-        } else {
-            token_id.to_string()
+    /// Whether `chain` accepts tickets from this chain, a chain without a counterparty list
+    /// accepts every chain.
+    pub fn is_counterparty_of(&self, chain: &Chain) -> bool {
+        chain
+            .counterparties
+            .as_ref()
+            .map_or(true, |counterparties| {
+                counterparties.contains(&self.chain_id)
+            })
+    }
+
+    /// Whether tickets can be sent to `chain_id`: a known counterparty, active and accepting
+    /// tickets from this chain.
+    pub fn check_target_chain(&self, chain_id: &str) -> Result<(), ContractError> {
+        let chain = self
+            .counterparties
+            .get(chain_id)
+            .ok_or(ContractError::TargetChainNotFound)?;
+        if chain.chain_state != ChainState::Active {
+            return Err(ContractError::TargetChainDeactive);
+        }
+        if !self.is_counterparty_of(chain) {
+            return Err(ContractError::TargetChainUnreachable);
+        }
+        Ok(())
+    }
+
+    /// Records the denom of tokens added before denoms were recorded, their factory denom
+    /// was derived from the token id.
+    pub fn migrate_token_denoms(&mut self, contract_addr: &Addr) {
+        for token_id in self.tokens.keys() {
+            if self.token_denoms.contains_key(token_id) {
+                continue;
+            }
+            let denom = self
+                .escrow_tokens
+                .get(token_id)
+                .cloned()
+                .unwrap_or_else(|| format!("factory/{}/{}", contract_addr, token_id));
+            self.token_denoms.insert(token_id.clone(), denom);
+        }
+    }
+
+    /// The existing denom `token_id` is bound to, escrowed or adopted, none when the contract
+    /// creates a factory denom for it.
+    pub fn bound_denom(&self, token_id: &str) -> Option<String> {
+        self.escrow_tokens
+            .get(token_id)
+            .or_else(|| self.adopted_denoms.get(token_id))
+            .cloned()
+    }
+
+    /// The bank denom of a token, recorded when the token was added.
+    pub fn token_denom(&self, token_id: &str) -> Result<String, ContractError> {
+        self.token_denoms
+            .get(token_id)
+            .cloned()
+            .ok_or(ContractError::TokenNotFound)
+    }
+
+    /// Whether redeems of a removed token are closed, tokens that were not removed are open.
+    pub fn is_sunset_over(&self, token_id: &str, env: &Env) -> bool {
+        self.removed_tokens
+            .get(token_id)
+            .is_some_and(|sunset| sunset.redeem_until <= env.block.time.seconds())
+    }
+
+    /// Coins of `denom` the contract holds for users: locked by escrowed tokens, live or
+    /// archived.
+    pub fn escrowed_amount(&self, denom: &str) -> u128 {
+        let live: u128 = self
+            .escrow_tokens
+            .iter()
+            .filter(|(_, escrow_denom)| *escrow_denom == denom)
+            .map(|(token_id, _)| {
+                self.escrow_balances
+                    .get(token_id)
+                    .cloned()
+                    .unwrap_or_default()
+            })
+            .sum();
+        let archived: u128 = self
+            .archived_escrow
+            .values()
+            .filter(|coin| coin.denom == denom)
+            .map(|coin| coin.amount.u128())
+            .sum();
+        live + archived
+    }
+
+    pub fn timelock_delay(&self) -> u64 {
+        self.timelock_delay.unwrap_or(DEFAULT_TIMELOCK_SECS)
+    }
+
+    pub fn max_memo_length(&self) -> u32 {
+        self.max_memo_length.unwrap_or(DEFAULT_MAX_MEMO_LENGTH)
+    }
+
+    /// Moves the legacy runes mapping into `token_ids` and registers every other token
+    /// under its own id.
+    pub fn migrate_token_ids(&mut self) -> Result<(), ContractError> {
+        for (omnity_id, local_id) in std::mem::take(&mut self.runes_replaced_id_map) {
+            self.token_ids.insert(&omnity_id, &local_id)?;
+        }
+        for token_id in self.tokens.keys() {
+            if self.token_ids.omnity_id(token_id) == *token_id {
+                self.token_ids.insert(token_id, token_id)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// ICP chain-key token `token_id` stands for: the per token mapping, else the asset named
+    /// by the token id, else ckBTC for the configured ckBTC token.
+    pub fn chain_key_token(&self, token_id: &str) -> Option<IcpChainKeyToken> {
+        self.chain_key_tokens
+            .get(token_id)
+            .cloned()
+            .or_else(|| {
+                IcpChainKeyToken::from_asset(self.token_ids.omnity_id(token_id).rsplit('-').next()?)
+            })
+            .or_else(|| {
+                (!self.ckbtc_token_id.is_empty() && self.ckbtc_token_id == token_id)
+                    .then_some(IcpChainKeyToken::CKBTC)
+            })
+    }
+
+    /// The action a ticket redeeming the token to `target_chain` carries, for `RedeemToken` and
+    /// `RedeemAllBTC` alike. Escrowed tokens settle on this chain and are transferred. ICP
+    /// chain-key tokens redeem their native asset wherever they are sent. Other tokens are
+    /// plainly redeemed when sent to their settlement chain and transferred anywhere else.
+    pub fn redeem_action(&self, token_id: &str, target_chain: &str) -> TxAction {
+        if self.escrow_tokens.contains_key(token_id) {
+            return TxAction::Transfer;
+        }
+        if let Some(chain_key_token) = self.chain_key_token(token_id) {
+            return TxAction::RedeemIcpChainKeyAssets(chain_key_token);
+        }
+
+        let omnity_id = self.token_ids.omnity_id(token_id);
+        let settlement_chain = self
+            .tokens
+            .get(token_id)
+            .and_then(|token| token.settlement_chain())
+            .unwrap_or_else(|| omnity_id.split('-').next().unwrap_or_default());
+        match settlement_chain == target_chain {
+            true => TxAction::Redeem,
+            false => TxAction::Transfer,
         }
     }
 }
 
 pub const STATE: Item<State> = Item::new("state");
 pub const GENERATE_TICKET_REQ: Map<u64, GenerateTicketReq> = Map::new("generate-ticket-req");
+// (granter, operator) -> approval for the operator to generate tickets on behalf of the granter
+pub const APPROVALS: Map<(&Addr, &Addr), Approval> = Map::new("approvals");
+
+// tokens taken out of `State::tokens` once their sunset ended, by local token id
+pub const ARCHIVED_TOKENS: Map<&str, ArchivedToken> = Map::new("archived-tokens");
+
+// timelocked admin operations waiting to be executed or cancelled, by operation id
+pub const PENDING_OPERATIONS: Map<u64, PendingOperation> = Map::new("pending-operations");
+
+// partial attestations of a mint, keyed by ticket id and `MintAttestation::digest`
+pub const MINT_ATTESTATIONS: Map<(&str, &str), MintAttestation> = Map::new("mint-attestations");
+
+// tickets relayed and rewards of each relayer of hub-signed tickets
+pub const RELAYERS: Map<&Addr, RelayerStats> = Map::new("relayers");
+
+// relayers keyed by their relayed tickets, so the leaderboard never sorts every relayer
+pub const RELAYER_RANKING: Map<(u64, &Addr), ()> = Map::new("relayer-ranking");
+
+// metadata changes of each token, keyed by token id and change sequence
+pub const TOKEN_METADATA_HISTORY: Map<(&str, u64), TokenMetadataChange> =
+    Map::new("token-metadata-history");
 
 pub fn read_state<F, R>(store: &dyn Storage, f: F) -> R
 where
@@ -57,6 +285,27 @@ where
     f(&STATE.load(store).expect("State not initialized!"))
 }
 
+/// Target chains a token may be sent to: a chain must be listed in `allow` when it is set
+/// and must not be listed in `deny`.
+#[cw_serde]
+#[derive(Default)]
+pub struct TokenRoutes {
+    pub allow: Option<BTreeSet<ChainId>>,
+    #[serde(default)]
+    pub deny: BTreeSet<ChainId>,
+}
+
+impl Eq for TokenRoutes {}
+
+impl TokenRoutes {
+    pub fn permits(&self, chain_id: &str) -> bool {
+        self.allow
+            .as_ref()
+            .map_or(true, |allow| allow.contains(chain_id))
+            && !self.deny.contains(chain_id)
+    }
+}
+
 #[cw_serde]
 pub struct GenerateTicketReq {
     pub seq: u64,
@@ -71,6 +320,230 @@ pub struct GenerateTicketReq {
     pub memo: Option<String>,
     pub fee_token: String,
     pub fee_amount: String,
+    // granter an approved operator generated the ticket for, the sender being the operator
+    // whose coins were burnt or locked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_behalf_of: Option<String>,
+    // local account a ticket that never reaches the hub is refunded to, when the sender is an
+    // account of another chain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refund_to: Option<String>,
+}
+
+#[cw_serde]
+pub struct TokenMetadataChange {
+    pub seq: u64,
+    pub updated_by: Addr,
+    // block time in seconds
+    pub timestamp: u64,
+    pub block_height: u64,
+    // none when the change added the token
+    pub previous: Option<Token>,
+    pub current: Token,
+}
+
+#[cw_serde]
+pub struct TokenSunset {
+    // block time in seconds
+    pub removed_at: u64,
+    pub redeem_until: u64,
+    pub denom_admin: Option<String>,
+    #[serde(default)]
+    pub escrow_receiver: Option<String>,
+}
+
+impl Eq for TokenSunset {}
+
+#[cw_serde]
+pub struct ArchivedToken {
+    pub token: Token,
+    pub omnity_id: String,
+    pub denom: String,
+    // block time in seconds
+    pub removed_at: u64,
+    pub archived_at: u64,
+    // new admin of the denom, handed over through a timelocked operation, none for an escrowed
+    // token whose denom the contract never owned
+    pub denom_admin: Option<String>,
+    // coins of an escrowed token still locked when it was archived, kept in `archived_escrow`
+    // until they are released to `escrow_receiver` through a timelocked operation
+    pub escrow_balance: u128,
+    #[serde(default)]
+    pub escrow_receiver: Option<String>,
+}
+
+/// Delay between scheduling a timelocked operation and executing it while the admin has not
+/// configured one, two days.
+pub const DEFAULT_TIMELOCK_SECS: u64 = 2 * 24 * 60 * 60;
+
+/// Shortest timelock delay the admin may configure, one hour.
+pub const MIN_TIMELOCK_SECS: u64 = 60 * 60;
+
+/// Sensitive actions only executed once the timelock delay has passed since they were
+/// scheduled, leaving time to cancel them.
+#[cw_serde]
+pub enum TimelockedAction {
+    UpdateRoute {
+        route: Addr,
+    },
+    RefundToken {
+        denom: String,
+        receiver: String,
+        amount: String,
+    },
+    // the delay of operations scheduled after this one, none restores the default
+    SetTimelockDelay {
+        delay_secs: Option<u64>,
+    },
+    // none stops rewarding relayers
+    SetRelayReward {
+        reward: Option<Uint128>,
+    },
+    // none stops accepting IBC channels and closes the open one
+    SetIbcPort {
+        port: Option<CounterpartPort>,
+    },
+    // none switches mints back to the route
+    SetAttestors {
+        attestors: Option<AttestorSet>,
+    },
+    // scheduled by the `UpdateHubKey` directive, none stops accepting hub signatures
+    SetHubKey {
+        hub_key: Option<HubKey>,
+    },
+    // scheduled by `ArchiveToken`, pays the escrow left by an archived token to the receiver
+    // named in its `RemoveToken` directive
+    ReleaseArchivedEscrow {
+        token_id: TokenId,
+        receiver: String,
+    },
+    // hand the tokenfactory denom admin of the tokens over to `new_admin`
+    TransferDenomAdmin {
+        token_ids: Vec<TokenId>,
+        new_admin: String,
+    },
+}
+
+#[cw_serde]
+pub struct CounterpartPort {
+    // connection the channel must be opened on, its client pins the counterpart chain
+    pub connection_id: String,
+    pub port_id: String,
+}
+
+impl Eq for CounterpartPort {}
+
+#[cw_serde]
+pub struct PendingOperation {
+    pub id: u64,
+    pub action: TimelockedAction,
+    pub scheduled_by: Addr,
+    // block time in seconds
+    pub scheduled_at: u64,
+    pub executable_at: u64,
+}
+
+/// Addresses that must each attest the same mint, `threshold` of them, within `expiry_secs`
+/// of the first attestation.
+#[cw_serde]
+pub struct AttestorSet {
+    pub attestors: BTreeSet<Addr>,
+    pub threshold: u32,
+    pub expiry_secs: u64,
+}
+
+impl Eq for AttestorSet {}
+
+impl AttestorSet {
+    pub fn validate(&self) -> Result<(), ContractError> {
+        if self.threshold == 0 || self.threshold as usize > self.attestors.len() {
+            return Err(ContractError::InvalidAttestors(format!(
+                "threshold {} of {} attestors",
+                self.threshold,
+                self.attestors.len()
+            )));
+        }
+        if self.expiry_secs == 0 {
+            return Err(ContractError::InvalidAttestors(
+                "expiry_secs is zero".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cw_serde]
+pub struct MintAttestation {
+    pub ticket_id: String,
+    pub token_id: TokenId,
+    pub receiver: Addr,
+    pub amount: String,
+    pub transmuter: Option<String>,
+    #[serde(default)]
+    pub channel: Option<String>,
+    #[serde(default)]
+    pub recovery: Option<Addr>,
+    pub attestors: BTreeSet<Addr>,
+    // block time in seconds
+    pub expires_at: u64,
+}
+
+impl MintAttestation {
+    /// Identifies what is attested besides the ticket id, attestors disagreeing on any field
+    /// attest different mints.
+    pub fn digest(&self) -> String {
+        let mut fields = vec![
+            self.token_id.as_str(),
+            self.receiver.as_str(),
+            self.amount.as_str(),
+            self.transmuter.as_deref().unwrap_or_default(),
+        ];
+        // absent for tickets that are not forwarded, keeping their digests unchanged
+        fields.extend(self.channel.as_deref());
+        fields.extend(self.recovery.as_ref().map(Addr::as_str));
+        let mut hasher = Sha256::new();
+        for field in fields {
+            // length prefixed so fields cannot be shifted into each other
+            hasher.update((field.len() as u64).to_be_bytes());
+            hasher.update(field.as_bytes());
+        }
+        HexBinary::from(hasher.finalize().as_slice()).to_hex()
+    }
+
+    pub fn is_expired(&self, env: &Env) -> bool {
+        self.expires_at <= env.block.time.seconds()
+    }
+}
+
+/// Ledger entry of a relayer, rewards are in the fee token.
+#[cw_serde]
+pub struct RelayerStats {
+    pub relayer: Addr,
+    pub tickets: u64,
+    pub earned: Uint128,
+    pub paid: Uint128,
+}
+
+impl RelayerStats {
+    pub fn owed(&self) -> Uint128 {
+        self.earned - self.paid
+    }
+}
+
+#[cw_serde]
+pub struct Approval {
+    pub granter: Addr,
+    pub operator: Addr,
+    // block time in seconds
+    pub granted_at: u64,
+    pub expires_at: Option<u64>,
+}
+
+impl Approval {
+    pub fn is_expired(&self, env: &Env) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= env.block.time.seconds())
+    }
 }
 
 #[cw_serde]
@@ -83,5 +556,173 @@ pub enum TxAction {
 
 #[cw_serde]
 pub enum IcpChainKeyToken {
-    CKBTC
-}
\ No newline at end of file
+    CKBTC,
+    CKETH,
+    CKUSDT,
+}
+
+impl Eq for IcpChainKeyToken {}
+
+impl IcpChainKeyToken {
+    /// The chain-key token named by the last segment of an Omnity token id, such as
+    /// `sICP-icrc-ckETH`.
+    pub fn from_asset(asset: &str) -> Option<Self> {
+        match asset.to_ascii_lowercase().as_str() {
+            "ckbtc" => Some(Self::CKBTC),
+            "cketh" => Some(Self::CKETH),
+            "ckusdt" => Some(Self::CKUSDT),
+            _ => None,
+        }
+    }
+}
+#[test]
+pub fn test_redeem_action() {
+    use crate::{contract::instantiate, msg::InstantiateMsg};
+    use cosmwasm_std::testing::{message_info, mock_dependencies, mock_env, MockApi};
+
+    let mut deps = mock_dependencies();
+    let api = MockApi::default();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&api.addr_make("admin"), &[]),
+        InstantiateMsg {
+            route: api.addr_make("route"),
+            chain_id: "osmosis-1".to_string(),
+        },
+    )
+    .unwrap();
+    let mut state = STATE.load(&deps.storage).unwrap();
+    let mut add_token = |omnity_id: &str, metadata: HashMap<String, String>| {
+        let token_id = state.token_ids.register(omnity_id).unwrap();
+        state.tokens.insert(
+            token_id.clone(),
+            Token {
+                token_id: token_id.clone(),
+                name: omnity_id.to_string(),
+                symbol: omnity_id.to_string(),
+                decimals: 8,
+                icon: None,
+                metadata,
+            },
+        );
+        token_id
+    };
+    let cketh = add_token("sICP-icrc-ckETH", HashMap::new());
+    let ckusdt = add_token("sICP-icrc-ckUSDT", HashMap::new());
+    let icp = add_token("sICP-native-ICP", HashMap::new());
+    let rune = add_token("Bitcoin-runes-HOPE•YOU•GET•RICH", HashMap::new());
+    let bridged = add_token(
+        "wrapped-USDC",
+        HashMap::from([(
+            crate::route::metadata_key::SETTLEMENT_CHAIN.to_string(),
+            "Ethereum".to_string(),
+        )]),
+    );
+    let osmo = add_token("osmosis-native-OSMO", HashMap::new());
+    state
+        .escrow_tokens
+        .insert(osmo.clone(), "uosmo".to_string());
+
+    // chain-key tokens redeem their native asset wherever they are sent
+    for target_chain in ["Ethereum", "sICP"] {
+        assert_eq!(
+            state.redeem_action(&cketh, target_chain),
+            TxAction::RedeemIcpChainKeyAssets(IcpChainKeyToken::CKETH)
+        );
+        assert_eq!(
+            state.redeem_action(&ckusdt, target_chain),
+            TxAction::RedeemIcpChainKeyAssets(IcpChainKeyToken::CKUSDT)
+        );
+    }
+    // tokens sent to their settlement chain are redeemed
+    assert_eq!(state.redeem_action(&icp, "sICP"), TxAction::Redeem);
+    assert_eq!(state.redeem_action(&rune, "Bitcoin"), TxAction::Redeem);
+    // the settlement chain recorded in the metadata wins over the id
+    assert_eq!(state.redeem_action(&bridged, "Ethereum"), TxAction::Redeem);
+    // anywhere else, and escrowed tokens, are transfers
+    assert_eq!(state.redeem_action(&rune, "sICP"), TxAction::Transfer);
+    assert_eq!(state.redeem_action(&bridged, "wrapped"), TxAction::Transfer);
+    assert_eq!(state.redeem_action(&osmo, "osmosis"), TxAction::Transfer);
+
+    // the per token mapping takes precedence
+    state
+        .chain_key_tokens
+        .insert(icp.clone(), IcpChainKeyToken::CKBTC);
+    state
+        .chain_key_tokens
+        .insert(ckusdt.clone(), IcpChainKeyToken::CKETH);
+    assert_eq!(
+        state.redeem_action(&icp, "sICP"),
+        TxAction::RedeemIcpChainKeyAssets(IcpChainKeyToken::CKBTC)
+    );
+    assert_eq!(
+        state.redeem_action(&ckusdt, "Ethereum"),
+        TxAction::RedeemIcpChainKeyAssets(IcpChainKeyToken::CKETH)
+    );
+}
+
+#[test]
+pub fn test_ckbtc_redeem_action() {
+    use crate::{contract::instantiate, msg::InstantiateMsg};
+    use cosmwasm_std::testing::{message_info, mock_dependencies, mock_env, MockApi};
+
+    let mut deps = mock_dependencies();
+    let api = MockApi::default();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&api.addr_make("admin"), &[]),
+        InstantiateMsg {
+            route: api.addr_make("route"),
+            chain_id: "osmosis-1".to_string(),
+        },
+    )
+    .unwrap();
+    let mut state = STATE.load(&deps.storage).unwrap();
+    let ckbtc = state.token_ids.register("sICP-icrc-ckBTC").unwrap();
+    state.tokens.insert(
+        ckbtc.clone(),
+        Token {
+            token_id: ckbtc.clone(),
+            name: "ckBTC".to_string(),
+            symbol: "ckBTC".to_string(),
+            decimals: 8,
+            icon: None,
+            metadata: HashMap::new(),
+        },
+    );
+    state.ckbtc_token_id = ckbtc.clone();
+
+    // the action `redeem_allbtc_ticket_req` gives ckBTC too, whatever the target
+    for target_chain in ["Bitcoin", "sICP"] {
+        assert_eq!(
+            state.redeem_action(&ckbtc, target_chain),
+            TxAction::RedeemIcpChainKeyAssets(IcpChainKeyToken::CKBTC)
+        );
+    }
+}
+
+#[test]
+pub fn test_legacy_ticket_req_serialization() {
+    let req = GenerateTicketReq {
+        seq: 1,
+        target_chain_id: "Bitcoin".to_string(),
+        sender: "osmo1sender".to_string(),
+        receiver: "bc1receiver".to_string(),
+        token_id: "Bitcoin-runes-HOPE".to_string(),
+        amount: "100".to_string(),
+        action: TxAction::Redeem,
+        timestamp: 0,
+        block_height: 0,
+        memo: None,
+        fee_token: "uosmo".to_string(),
+        fee_amount: "0".to_string(),
+        on_behalf_of: None,
+        refund_to: None,
+    };
+    let json = serde_json::to_string(&req).unwrap();
+    assert!(!json.contains("on_behalf_of"));
+    assert!(!json.contains("refund_to"));
+    assert_eq!(serde_json::from_str::<GenerateTicketReq>(&json).unwrap(), req);
+}
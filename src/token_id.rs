@@ -0,0 +1,187 @@
+use std::collections::BTreeMap;
+
+use cosmwasm_std::HexBinary;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::ContractError;
+
+/// Longest subdenom tokenfactory accepts.
+pub const MAX_SUBDENOM_LENGTH: usize = 44;
+/// Readable part kept in front of the hash of an id that cannot be used as is.
+const HASHED_PREFIX_LENGTH: usize = 20;
+const HASH_LENGTH: usize = MAX_SUBDENOM_LENGTH - HASHED_PREFIX_LENGTH - 1;
+
+/// Whether `subdenom` can be used as a tokenfactory subdenom as is.
+pub fn is_valid_subdenom(subdenom: &str) -> bool {
+    !subdenom.is_empty()
+        && subdenom.len() <= MAX_SUBDENOM_LENGTH
+        && subdenom
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+}
+
+/// The local id of an Omnity token id, which is also its tokenfactory subdenom. Runes
+/// spacers `•` become `.`, an id that is still not a valid subdenom is replaced by its
+/// sanitized prefix followed by a sha256 digest of the full id.
+pub fn normalize_token_id(token_id: &str) -> String {
+    let replaced = token_id.replace('•', ".");
+    if is_valid_subdenom(&replaced) {
+        return replaced;
+    }
+
+    let prefix: String = replaced
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+        .take(HASHED_PREFIX_LENGTH)
+        .collect();
+    let digest = HexBinary::from(Sha256::digest(token_id.as_bytes()).as_slice()).to_hex();
+    format!("{}.{}", prefix, &digest[..HASH_LENGTH])
+}
+
+/// Both directions between the Omnity ids of the tokens and their local ids.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, JsonSchema)]
+pub struct TokenIdMap {
+    // omnity token id -> local token id
+    local_ids: BTreeMap<String, String>,
+    // local token id -> omnity token id
+    omnity_ids: BTreeMap<String, String>,
+}
+
+impl TokenIdMap {
+    /// Records `omnity_id` and returns its local id. Registering an id again is a no-op,
+    /// two ids normalizing to the same local id are rejected.
+    pub fn register(&mut self, omnity_id: &str) -> Result<String, ContractError> {
+        let local_id = normalize_token_id(omnity_id);
+        self.insert(omnity_id, &local_id)?;
+        Ok(local_id)
+    }
+
+    /// Records a known pair as is, used to import mappings made before normalization.
+    pub fn insert(&mut self, omnity_id: &str, local_id: &str) -> Result<(), ContractError> {
+        if let Some(existing) = self.omnity_ids.get(local_id) {
+            if existing != omnity_id {
+                return Err(ContractError::TokenIdConflict(
+                    omnity_id.to_string(),
+                    existing.clone(),
+                ));
+            }
+        }
+        if let Some(existing) = self.local_ids.get(omnity_id) {
+            if existing != local_id {
+                return Err(ContractError::TokenIdConflict(
+                    omnity_id.to_string(),
+                    existing.clone(),
+                ));
+            }
+        }
+        self.local_ids
+            .insert(omnity_id.to_string(), local_id.to_string());
+        self.omnity_ids
+            .insert(local_id.to_string(), omnity_id.to_string());
+        Ok(())
+    }
+
+    /// Forgets `local_id` and the Omnity id mapped to it.
+    pub fn remove(&mut self, local_id: &str) {
+        if let Some(omnity_id) = self.omnity_ids.remove(local_id) {
+            self.local_ids.remove(&omnity_id);
+        }
+    }
+
+    /// Resolves a token id given in either form to its local id.
+    pub fn local_id(&self, token_id: &str) -> String {
+        match self.local_ids.get(token_id) {
+            Some(local_id) => local_id.clone(),
+            None => normalize_token_id(token_id),
+        }
+    }
+
+    /// The Omnity id of `local_id`, ids without a recorded mapping are their own Omnity id.
+    pub fn omnity_id(&self, local_id: &str) -> String {
+        self.omnity_ids
+            .get(local_id)
+            .cloned()
+            .unwrap_or_else(|| local_id.to_string())
+    }
+}
+
+#[test]
+pub fn test_valid_ids_are_kept() {
+    for token_id in [
+        "sICP-icrc-ckBTC",
+        "osmosis-native-OSMO",
+        "Bitcoin-runes-HOPE.YOU.GET.RICH",
+        "a",
+        "under_score",
+        &"x".repeat(MAX_SUBDENOM_LENGTH),
+    ] {
+        assert!(is_valid_subdenom(token_id), "{}", token_id);
+        assert_eq!(normalize_token_id(token_id), token_id);
+    }
+}
+
+#[test]
+pub fn test_runes_spacers_are_replaced() {
+    assert_eq!(
+        normalize_token_id("Bitcoin-runes-HOPE•YOU•GET•RICH"),
+        "Bitcoin-runes-HOPE.YOU.GET.RICH"
+    );
+    assert_eq!(normalize_token_id("•"), ".");
+}
+
+#[test]
+pub fn test_invalid_ids_are_hashed() {
+    let long = "Bitcoin-runes-DOG•GO•TO•THE•MOON•AND•BACK•AGAIN";
+    let spaced = "Ethereum-erc20-Wrapped Ether";
+    let slashed = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+    for token_id in [long, spaced, slashed, "", "€€€", &"x".repeat(45)] {
+        let local_id = normalize_token_id(token_id);
+        assert!(is_valid_subdenom(&local_id), "{} -> {}", token_id, local_id);
+        assert!(local_id.len() <= MAX_SUBDENOM_LENGTH);
+        // deterministic
+        assert_eq!(local_id, normalize_token_id(token_id));
+    }
+
+    assert_eq!(
+        normalize_token_id(long),
+        format!(
+            "Bitcoin-runes-DOG.GO.{}",
+            &normalize_token_id(long)[HASHED_PREFIX_LENGTH + 1..]
+        )
+    );
+    assert_eq!(normalize_token_id(spaced).len(), MAX_SUBDENOM_LENGTH);
+    assert!(normalize_token_id("€€€").starts_with('.'));
+    // ids differing only after the prefix do not collide
+    assert_ne!(
+        normalize_token_id(&format!("{}A", "x".repeat(45))),
+        normalize_token_id(&format!("{}B", "x".repeat(45)))
+    );
+}
+
+#[test]
+pub fn test_token_id_map() {
+    let mut map = TokenIdMap::default();
+    let runes = "Bitcoin-runes-HOPE•YOU•GET•RICH";
+    let local_id = map.register(runes).unwrap();
+    assert_eq!(local_id, "Bitcoin-runes-HOPE.YOU.GET.RICH");
+    assert_eq!(map.register(runes).unwrap(), local_id);
+
+    assert_eq!(map.local_id(runes), local_id);
+    assert_eq!(map.local_id(&local_id), local_id);
+    assert_eq!(map.omnity_id(&local_id), runes);
+    assert_eq!(map.local_id("osmosis-native-OSMO"), "osmosis-native-OSMO");
+    assert_eq!(map.omnity_id("osmosis-native-OSMO"), "osmosis-native-OSMO");
+
+    // the dotted id would take the local id of the runes token
+    assert!(matches!(
+        map.register("Bitcoin-runes-HOPE.YOU.GET.RICH"),
+        Err(ContractError::TokenIdConflict(_, _))
+    ));
+    assert!(map.insert(runes, "other").is_err());
+
+    map.remove(&local_id);
+    assert_eq!(map.omnity_id(&local_id), local_id);
+    assert!(map.register("Bitcoin-runes-HOPE.YOU.GET.RICH").is_ok());
+}
@@ -1,6 +1,36 @@
 use cosmwasm_std::Addr;
 use serde::{Deserialize, Serialize};
 
+use crate::state::TxAction;
+
+
+/// A ticket to mint on this port, as the hub signs it and attestors attest it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MintTicket {
+    pub ticket_id: String,
+    pub token_id: String,
+    pub receiver: Addr,
+    pub amount: String,
+    pub transmuter: Option<String>,
+    // channel the minted tokens are forwarded to `receiver` over
+    pub channel: Option<String>,
+    // local account credited if the forward fails, instead of the receiver's local account
+    pub recovery: Option<Addr>,
+}
+
+/// An outbound ticket as it was asked for, before it is checked and priced.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TicketParams {
+    pub token_id: String,
+    pub sender: String,
+    pub receiver: String,
+    pub amount: String,
+    pub target_chain: String,
+    pub action: TxAction,
+    pub memo: Option<String>,
+    // sent by an ibc hook, the fee is paid in the transferred coin
+    pub from_hook: bool,
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MintTokenPayload {